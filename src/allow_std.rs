@@ -0,0 +1,261 @@
+use crate::{block_on, error::into_io_error, AsyncRead, AsyncWrite, Blocking, Classify};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{
+    error::Error,
+    io::{self, BufRead, ErrorKind, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write},
+};
+
+macro_rules! retry {
+    ($cx:expr, $e:expr) => {
+        loop {
+            match $e {
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                    $cx.waker().wake_by_ref();
+                    break Poll::Pending;
+                }
+                res => break Poll::Ready(res),
+            }
+        }
+    };
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AllowStdIo<T>(T);
+
+impl<T> Unpin for AllowStdIo<T> {}
+
+impl<T> AllowStdIo<T> {
+    pub fn new(io: T) -> Self {
+        AllowStdIo(io)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Read> AsyncRead for AllowStdIo<T> {
+    type Error = io::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.get_mut();
+        retry!(cx, me.0.read(buf))
+    }
+}
+
+impl<T: Write> AsyncWrite for AllowStdIo<T> {
+    type WriteError = io::Error;
+    type FlushError = io::Error;
+    type CloseError = io::Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        let me = self.get_mut();
+        retry!(cx, me.0.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        let me = self.get_mut();
+        retry!(cx, me.0.flush())
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<T: Read> Read for AllowStdIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+}
+
+impl<T: BufRead> BufRead for AllowStdIo<T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+impl<T: Write> Write for AllowStdIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<T: Seek> Seek for AllowStdIo<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl<T: Unpin + AsyncRead> Read for Blocking<T>
+where
+    T::Error: Classify + Into<Box<dyn Error + Send + Sync>>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        block_on(crate::AsyncReadExt::read(self.get_mut(), buf)).map_err(into_io_error)
+    }
+}
+
+impl<T: Unpin + AsyncWrite> Write for Blocking<T>
+where
+    T::WriteError: Classify + Into<Box<dyn Error + Send + Sync>>,
+    T::FlushError: Classify + Into<Box<dyn Error + Send + Sync>>,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        block_on(crate::AsyncWriteExt::write(self.get_mut(), buf)).map_err(into_io_error)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        block_on(crate::AsyncWriteExt::flush(self.get_mut())).map_err(into_io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use _futures::task::{waker, ArcWake};
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    /// Fails with each scripted error kind in turn, then succeeds.
+    struct Flaky(VecDeque<ErrorKind>);
+
+    impl Flaky {
+        fn new(kinds: &[ErrorKind]) -> Self {
+            Flaky(kinds.iter().copied().collect())
+        }
+
+        fn next(&mut self) -> io::Result<()> {
+            match self.0.pop_front() {
+                Some(kind) => Err(kind.into()),
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.next()?;
+            buf[..2].copy_from_slice(b"ok");
+            Ok(2)
+        }
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.next().map(|()| buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.next()
+        }
+    }
+
+    struct Count(AtomicUsize);
+
+    impl ArcWake for Count {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn interrupted_is_retried() {
+        let count = Arc::new(Count(AtomicUsize::new(0)));
+        let waker = waker(count.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut io = AllowStdIo::new(Flaky::new(&[ErrorKind::Interrupted; 3]));
+
+        let mut buf = [0; 4];
+        match Pin::new(&mut io).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(2)) => assert_eq!(&buf[..2], b"ok"),
+            other => panic!("expected Ready(Ok(2)), got {:?}", other),
+        }
+        io.get_mut().0.extend([ErrorKind::Interrupted; 2]);
+        assert!(matches!(
+            Pin::new(&mut io).poll_write(&mut cx, b"abc"),
+            Poll::Ready(Ok(3))
+        ));
+        io.get_mut().0.push_back(ErrorKind::Interrupted);
+        assert!(matches!(
+            Pin::new(&mut io).poll_flush(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+        assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn would_block_is_pending_and_wakes() {
+        let count = Arc::new(Count(AtomicUsize::new(0)));
+        let waker = waker(count.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut io = AllowStdIo::new(Flaky::new(&[ErrorKind::WouldBlock]));
+
+        let mut buf = [0; 4];
+        assert!(Pin::new(&mut io).poll_read(&mut cx, &mut buf).is_pending());
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            Pin::new(&mut io).poll_read(&mut cx, &mut buf),
+            Poll::Ready(Ok(2))
+        ));
+
+        io.get_mut().0.push_back(ErrorKind::WouldBlock);
+        assert!(Pin::new(&mut io).poll_write(&mut cx, b"abc").is_pending());
+        assert_eq!(count.0.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            Pin::new(&mut io).poll_write(&mut cx, b"abc"),
+            Poll::Ready(Ok(3))
+        ));
+    }
+
+    #[test]
+    fn other_errors_are_returned() {
+        let mut cx = Context::from_waker(_futures::task::noop_waker_ref());
+        let mut io = AllowStdIo::new(Flaky::new(&[ErrorKind::InvalidData]));
+        match Pin::new(&mut io).poll_read(&mut cx, &mut [0; 4]) {
+            Poll::Ready(Err(e)) => assert_eq!(e.kind(), ErrorKind::InvalidData),
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+}
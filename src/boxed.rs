@@ -0,0 +1,138 @@
+use crate::{AsyncRead, AsyncWrite};
+
+/// A reader that is also a writer, so both halves can sit behind one trait object.
+pub trait AsyncDuplex: AsyncRead + AsyncWrite {}
+
+impl<T: AsyncRead + AsyncWrite + ?Sized> AsyncDuplex for T {}
+
+#[cfg(feature = "alloc")]
+mod aliases {
+    use super::*;
+    use crate::BoxError;
+    use alloc::boxed::Box;
+    use core::pin::Pin;
+
+    pub type BoxedReader<'a, E = BoxError> = Pin<Box<dyn AsyncRead<Error = E> + Send + 'a>>;
+
+    pub type LocalBoxedReader<'a, E = BoxError> = Pin<Box<dyn AsyncRead<Error = E> + 'a>>;
+
+    pub type BoxedWriter<'a, WE = BoxError, FE = WE, CE = WE> =
+        Pin<Box<dyn AsyncWrite<WriteError = WE, FlushError = FE, CloseError = CE> + Send + 'a>>;
+
+    pub type LocalBoxedWriter<'a, WE = BoxError, FE = WE, CE = WE> =
+        Pin<Box<dyn AsyncWrite<WriteError = WE, FlushError = FE, CloseError = CE> + 'a>>;
+
+    pub type BoxedDuplex<'a, E = BoxError> = Pin<
+        Box<dyn AsyncDuplex<Error = E, WriteError = E, FlushError = E, CloseError = E> + Send + 'a>,
+    >;
+
+    pub type LocalBoxedDuplex<'a, E = BoxError> =
+        Pin<Box<dyn AsyncDuplex<Error = E, WriteError = E, FlushError = E, CloseError = E> + 'a>>;
+}
+
+#[cfg(feature = "alloc")]
+pub use aliases::*;
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt, AsyncWriteExt};
+    use alloc::vec::Vec;
+    use core::{
+        marker::PhantomPinned,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use pin_project_lite::pin_project;
+    use void::Void;
+
+    pin_project! {
+        /// Reads back what was written to it; `!Unpin`, so it can only be used pinned.
+        struct Loopback {
+            data: Vec<u8>,
+            #[pin]
+            _pin: PhantomPinned,
+        }
+    }
+
+    fn loopback(data: &[u8]) -> Loopback {
+        Loopback {
+            data: data.into(),
+            _pin: PhantomPinned,
+        }
+    }
+
+    impl AsyncRead for Loopback {
+        type Error = Void;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let data = self.project().data;
+            let n = buf.len().min(data.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            data.drain(..n);
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    impl AsyncWrite for Loopback {
+        type WriteError = Void;
+        type FlushError = Void;
+        type CloseError = Void;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            self.project().data.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn read_all<E: core::fmt::Debug>(mut reader: BoxedReader<'_, E>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        block_on(reader.read_to_end(&mut buf)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn boxes_a_pinned_reader() {
+        assert_eq!(read_all(loopback(b"hello").boxed_reader()), b"hello");
+
+        let mut reader = loopback(b"hi").boxed_local_reader();
+        let mut buf = [0; 4];
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 2);
+    }
+
+    #[test]
+    fn boxes_a_pinned_reader_with_erased_errors() {
+        let reader: BoxedReader<'_> = loopback(b"hello").box_err().boxed_reader();
+        assert_eq!(read_all(reader), b"hello");
+    }
+
+    #[test]
+    fn boxes_a_pinned_duplex() {
+        let mut duplex: BoxedDuplex<'_, Void> = loopback(b"").boxed_duplex();
+        block_on(duplex.write_all(b"ping")).unwrap();
+        block_on(duplex.flush()).unwrap();
+        let mut buf = [0; 4];
+        block_on(duplex.read_exact(&mut buf)).unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let mut writer: LocalBoxedWriter<'_, Void> = loopback(b"").boxed_local_writer();
+        block_on(writer.write_all(b"pong")).unwrap();
+        block_on(writer.close()).unwrap();
+    }
+}
@@ -0,0 +1,339 @@
+use core::{fmt, mem::MaybeUninit, slice};
+
+pub struct BorrowedBuf<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    filled: usize,
+    initialized: usize,
+}
+
+impl<'a> BorrowedBuf<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        let initialized = buf.len();
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        BorrowedBuf {
+            buf,
+            filled: 0,
+            initialized,
+        }
+    }
+
+    pub fn uninit(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        BorrowedBuf {
+            buf,
+            filled: 0,
+            initialized: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.filled
+    }
+
+    pub fn filled(&self) -> &[u8] {
+        unsafe { slice_assume_init(&self.buf[..self.filled]) }
+    }
+
+    pub fn filled_mut(&mut self) -> &mut [u8] {
+        unsafe { slice_assume_init_mut(&mut self.buf[..self.filled]) }
+    }
+
+    pub fn initialized(&self) -> &[u8] {
+        unsafe { slice_assume_init(&self.buf[..self.initialized]) }
+    }
+
+    pub fn initialized_mut(&mut self) -> &mut [u8] {
+        unsafe { slice_assume_init_mut(&mut self.buf[..self.initialized]) }
+    }
+
+    /// # Safety
+    ///
+    /// The caller must not write uninitialized bytes into the returned slice.
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        &mut self.buf[self.filled..]
+    }
+
+    pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+        self.initialize_unfilled_to(self.remaining())
+    }
+
+    pub fn initialize_unfilled_to(&mut self, n: usize) -> &mut [u8] {
+        assert!(self.remaining() >= n, "n overflows remaining");
+
+        let end = self.filled + n;
+        if self.initialized < end {
+            for byte in &mut self.buf[self.initialized..end] {
+                *byte = MaybeUninit::new(0);
+            }
+            self.initialized = end;
+        }
+
+        unsafe { slice_assume_init_mut(&mut self.buf[self.filled..end]) }
+    }
+
+    pub fn clear(&mut self) {
+        self.filled = 0;
+    }
+
+    pub fn advance(&mut self, n: usize) {
+        let filled = self.filled.checked_add(n).expect("filled overflow");
+        self.set_filled(filled);
+    }
+
+    pub fn set_filled(&mut self, n: usize) {
+        assert!(
+            n <= self.initialized,
+            "filled must not become larger than initialized"
+        );
+        self.filled = n;
+    }
+
+    /// # Safety
+    ///
+    /// The first `n` unfilled bytes of the buffer must already be initialized.
+    pub unsafe fn assume_init(&mut self, n: usize) {
+        let new = self.filled + n;
+        if new > self.initialized {
+            self.initialized = new;
+        }
+    }
+
+    pub fn put_slice(&mut self, buf: &[u8]) {
+        assert!(
+            self.remaining() >= buf.len(),
+            "buf.len() must fit in remaining()"
+        );
+
+        let end = self.filled + buf.len();
+        for (dst, src) in self.buf[self.filled..end].iter_mut().zip(buf) {
+            *dst = MaybeUninit::new(*src);
+        }
+        if self.initialized < end {
+            self.initialized = end;
+        }
+        self.filled = end;
+    }
+
+    /// Returns a cursor over the unfilled part of the buffer.
+    ///
+    /// Bytes written through the cursor are appended to [`filled`](BorrowedBuf::filled).
+    pub fn unfilled<'this>(&'this mut self) -> BorrowedCursor<'this> {
+        BorrowedCursor {
+            start: self.filled,
+            // Shortening the data lifetime is fine because the cursor never hands out the
+            // `BorrowedBuf` itself, so nothing shorter-lived can be stored into it.
+            buf: unsafe { &mut *(self as *mut BorrowedBuf<'a>).cast::<BorrowedBuf<'this>>() },
+        }
+    }
+}
+
+/// A writable view of the unfilled part of a [`BorrowedBuf`].
+///
+/// The cursor can only append to the buffer it was created from. It is passed by value, so an
+/// implementation can't swap in a different buffer, and the owner of the `BorrowedBuf` can
+/// trust its `filled` and `initialized` counts after the call.
+pub struct BorrowedCursor<'a> {
+    buf: &'a mut BorrowedBuf<'a>,
+    start: usize,
+}
+
+impl<'a> BorrowedCursor<'a> {
+    /// Reborrows the cursor, so it can be passed on without giving it up.
+    pub fn reborrow<'this>(&'this mut self) -> BorrowedCursor<'this> {
+        BorrowedCursor {
+            // See `BorrowedBuf::unfilled`.
+            buf: unsafe { &mut *(self.buf as *mut BorrowedBuf<'a>).cast::<BorrowedBuf<'this>>() },
+            start: self.start,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buf.remaining()
+    }
+
+    /// Number of bytes appended through this cursor and its reborrows.
+    pub fn written(&self) -> usize {
+        self.buf.filled - self.start
+    }
+
+    /// The initialized part of the unfilled buffer.
+    pub fn initialized(&self) -> &[u8] {
+        &self.buf.initialized()[self.buf.filled..]
+    }
+
+    pub fn initialized_mut(&mut self) -> &mut [u8] {
+        let filled = self.buf.filled;
+        &mut self.buf.initialized_mut()[filled..]
+    }
+
+    /// # Safety
+    ///
+    /// The caller must not write uninitialized bytes into the returned slice.
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        self.buf.unfilled_mut()
+    }
+
+    pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+        self.buf.initialize_unfilled()
+    }
+
+    pub fn initialize_unfilled_to(&mut self, n: usize) -> &mut [u8] {
+        self.buf.initialize_unfilled_to(n)
+    }
+
+    /// Marks the next `n` bytes as filled. They must already be initialized.
+    pub fn advance(&mut self, n: usize) {
+        self.buf.advance(n)
+    }
+
+    /// # Safety
+    ///
+    /// The first `n` unfilled bytes of the buffer must already be initialized.
+    pub unsafe fn assume_init(&mut self, n: usize) {
+        self.buf.assume_init(n)
+    }
+
+    pub fn put_slice(&mut self, buf: &[u8]) {
+        self.buf.put_slice(buf)
+    }
+}
+
+impl fmt::Debug for BorrowedCursor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedCursor")
+            .field("written", &self.written())
+            .field("initialized", &self.initialized().len())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+impl fmt::Debug for BorrowedBuf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedBuf")
+            .field("filled", &self.filled)
+            .field("initialized", &self.initialized)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+pub(crate) unsafe fn slice_assume_init(slice: &[MaybeUninit<u8>]) -> &[u8] {
+    slice::from_raw_parts(slice.as_ptr() as *const u8, slice.len())
+}
+
+pub(crate) unsafe fn slice_assume_init_mut(slice: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+    slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut u8, slice.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uninit_starts_empty() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let buf = BorrowedBuf::uninit(&mut storage);
+        assert_eq!(buf.capacity(), 8);
+        assert_eq!(buf.remaining(), 8);
+        assert!(buf.filled().is_empty());
+        assert!(buf.initialized().is_empty());
+    }
+
+    #[test]
+    fn initialized_buffer_can_be_filled() {
+        let mut storage = [7; 4];
+        let mut buf = BorrowedBuf::new(&mut storage);
+        assert_eq!(buf.initialized(), &[7; 4]);
+        buf.advance(3);
+        assert_eq!(buf.filled(), &[7; 3]);
+        assert_eq!(buf.remaining(), 1);
+    }
+
+    #[test]
+    fn initialize_unfilled_only_zeroes_new_bytes() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.initialize_unfilled_to(2).copy_from_slice(&[1, 2]);
+        assert_eq!(buf.initialized(), &[1, 2]);
+        assert_eq!(buf.initialize_unfilled_to(4), &[1, 2, 0, 0]);
+        buf.advance(1);
+        assert_eq!(buf.filled(), &[1]);
+        assert_eq!(buf.initialize_unfilled(), &[2, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(buf.initialized().len(), 8);
+    }
+
+    #[test]
+    fn put_slice_fills_and_initializes() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.put_slice(b"abc");
+        assert_eq!(buf.filled(), b"abc");
+        assert_eq!(buf.initialized(), b"abc");
+        buf.clear();
+        assert!(buf.filled().is_empty());
+        assert_eq!(buf.initialized(), b"abc");
+    }
+
+    #[test]
+    fn assume_init_is_relative_to_filled() {
+        let mut storage = [MaybeUninit::new(1); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.put_slice(b"ab");
+        unsafe {
+            buf.assume_init(3);
+        }
+        assert_eq!(buf.initialized().len(), 5);
+        unsafe {
+            buf.assume_init(1);
+        }
+        assert_eq!(buf.initialized().len(), 5);
+    }
+
+    #[test]
+    fn cursor_appends_to_filled() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.put_slice(b"ab");
+        {
+            let mut cursor = buf.unfilled();
+            assert_eq!(cursor.remaining(), 6);
+            assert!(cursor.initialized().is_empty());
+            cursor.put_slice(b"c");
+            cursor.reborrow().initialize_unfilled_to(3)[..2].copy_from_slice(b"de");
+            cursor.reborrow().advance(2);
+            assert_eq!(cursor.written(), 3);
+            assert_eq!(cursor.initialized(), &[0]);
+        }
+        assert_eq!(buf.filled(), b"abcde");
+        assert_eq!(buf.initialized().len(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "filled must not become larger than initialized")]
+    fn cursor_advance_past_initialized_panics() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.unfilled().advance(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "filled must not become larger than initialized")]
+    fn advance_past_initialized_panics() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.initialize_unfilled_to(2);
+        buf.advance(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "buf.len() must fit in remaining()")]
+    fn put_slice_overflow_panics() {
+        let mut storage = [MaybeUninit::uninit(); 2];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.put_slice(b"abc");
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{AsyncRead, BorrowedCursor};
+use _futures::ready;
+use bytes::{Buf, Bytes};
+use core::{
+    cmp,
+    ops::DerefMut,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use void::Void;
+
+pub trait AsyncBufRead: AsyncRead {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<&[u8], Self::Error>>;
+
+    fn consume(self: Pin<&mut Self>, amt: usize);
+
+    /// Returns up to `max` bytes of buffered data as a `Bytes`, consuming them.
+    ///
+    /// The default copies out of `poll_fill_buf`. Implementations backed by `Bytes` hand out
+    /// a reference-counted slice instead. An empty chunk means end of stream.
+    fn poll_read_bytes(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        max: usize,
+    ) -> Poll<Result<Bytes, Self::Error>> {
+        let chunk = {
+            let buf = ready!(self.as_mut().poll_fill_buf(cx))?;
+            Bytes::copy_from_slice(&buf[..cmp::min(buf.len(), max)])
+        };
+        self.consume(chunk.len());
+        Poll::Ready(Ok(chunk))
+    }
+}
+
+macro_rules! deref_async_buf_read {
+    () => {
+        fn poll_fill_buf(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<&[u8], Self::Error>> {
+            Pin::new(&mut **self.get_mut()).poll_fill_buf(cx)
+        }
+
+        fn consume(mut self: Pin<&mut Self>, amt: usize) {
+            Pin::new(&mut **self).consume(amt)
+        }
+
+        fn poll_read_bytes(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            max: usize,
+        ) -> Poll<Result<Bytes, Self::Error>> {
+            Pin::new(&mut **self).poll_read_bytes(cx, max)
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+mod boxed {
+    use super::*;
+    use alloc::boxed::Box;
+
+    impl<T: ?Sized + AsyncBufRead + Unpin> AsyncBufRead for Box<T> {
+        deref_async_buf_read!();
+    }
+}
+
+impl<T: ?Sized + AsyncBufRead + Unpin> AsyncBufRead for &mut T {
+    deref_async_buf_read!();
+}
+
+impl<P> AsyncBufRead for Pin<P>
+where
+    P: DerefMut + Unpin,
+    P::Target: AsyncBufRead,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<&[u8], Self::Error>> {
+        self.get_mut().as_mut().poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().as_mut().consume(amt)
+    }
+
+    fn poll_read_bytes(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        max: usize,
+    ) -> Poll<Result<Bytes, Self::Error>> {
+        self.get_mut().as_mut().poll_read_bytes(cx, max)
+    }
+}
+
+impl AsyncBufRead for &[u8] {
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<&[u8], Self::Error>> {
+        Poll::Ready(Ok(*self.get_mut()))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let me = self.get_mut();
+        *me = &me[amt..];
+    }
+}
+
+impl AsyncRead for Bytes {
+    type Error = Void;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.get_mut();
+        let len = cmp::min(me.len(), buf.len());
+        buf[..len].copy_from_slice(&me[..len]);
+        me.advance(len);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.get_mut();
+        let len = cmp::min(me.len(), buf.remaining());
+        buf.put_slice(&me[..len]);
+        me.advance(len);
+        Poll::Ready(Ok(len))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl AsyncBufRead for Bytes {
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<&[u8], Self::Error>> {
+        Poll::Ready(Ok(&self.get_mut()[..]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().advance(amt)
+    }
+
+    fn poll_read_bytes(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        max: usize,
+    ) -> Poll<Result<Bytes, Self::Error>> {
+        let me = self.get_mut();
+        let len = cmp::min(me.len(), max);
+        Poll::Ready(Ok(me.split_to(len)))
+    }
+}
+
+/// Reads from any [`Buf`].
+///
+/// Chunks taken with `poll_read_bytes` go through [`Buf::to_bytes`], so `Bytes` and
+/// `BytesMut` sources are handed out without copying. A chunk smaller than what is left moves
+/// the rest of the buffer into the reader in one piece and splits chunks off it from then on;
+/// for other buffer types that is a single copy of the remaining data.
+#[derive(Debug)]
+pub struct Reader<B> {
+    inner: B,
+    // Data moved out of `inner` by `poll_read_bytes`, read before anything left in `inner`.
+    taken: Bytes,
+}
+
+impl<B: Buf> Reader<B> {
+    pub fn new(inner: B) -> Self {
+        Reader {
+            inner,
+            taken: Bytes::new(),
+        }
+    }
+
+    /// The wrapped buffer. Data already moved into the reader by `poll_read_bytes` is not
+    /// part of it.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// See [`get_ref`](Reader::get_ref).
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped buffer along with any unread data moved out of it by
+    /// `poll_read_bytes`.
+    pub fn into_parts(self) -> (B, Bytes) {
+        (self.inner, self.taken)
+    }
+
+    /// See [`get_ref`](Reader::get_ref); use [`into_parts`](Reader::into_parts) to keep data
+    /// already moved out of the buffer.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    fn source(&mut self) -> &mut dyn Buf {
+        if self.taken.has_remaining() {
+            &mut self.taken
+        } else {
+            &mut self.inner
+        }
+    }
+}
+
+impl<B: Buf + Unpin> AsyncRead for Reader<B> {
+    type Error = Void;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let source = self.get_mut().source();
+        let len = cmp::min(source.remaining(), buf.len());
+        source.copy_to_slice(&mut buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let source = self.get_mut().source();
+        let mut n = 0;
+        while source.has_remaining() && buf.remaining() > 0 {
+            let chunk = source.bytes();
+            let len = cmp::min(chunk.len(), buf.remaining());
+            buf.put_slice(&chunk[..len]);
+            source.advance(len);
+            n += len;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.taken.len().saturating_add(self.inner.remaining());
+        (len, Some(len))
+    }
+}
+
+impl<B: Buf + Unpin> AsyncBufRead for Reader<B> {
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<&[u8], Self::Error>> {
+        let me = self.get_mut();
+        if me.taken.has_remaining() {
+            Poll::Ready(Ok(&me.taken[..]))
+        } else {
+            Poll::Ready(Ok(me.inner.bytes()))
+        }
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().source().advance(amt)
+    }
+
+    fn poll_read_bytes(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        max: usize,
+    ) -> Poll<Result<Bytes, Self::Error>> {
+        let me = self.get_mut();
+        if me.taken.is_empty() {
+            if max >= me.inner.remaining() {
+                return Poll::Ready(Ok(me.inner.to_bytes()));
+            }
+            me.taken = me.inner.to_bytes();
+        }
+        let len = cmp::min(me.taken.len(), max);
+        Poll::Ready(Ok(me.taken.split_to(len)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_on;
+    use core::future::poll_fn;
+
+    fn read_bytes<R: AsyncBufRead<Error = Void> + Unpin>(reader: &mut R, max: usize) -> Bytes {
+        block_on(poll_fn(|cx| {
+            Pin::new(&mut *reader).poll_read_bytes(cx, max)
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn chunked_reads_share_the_allocation() {
+        let data = Bytes::from(&b"hello world"[..]);
+        let start = data.as_ptr();
+        let mut reader = Reader::new(data);
+
+        let first = read_bytes(&mut reader, 5);
+        let second = read_bytes(&mut reader, 3);
+        let rest = read_bytes(&mut reader, 100);
+        assert_eq!(
+            (&first[..], &second[..], &rest[..]),
+            (&b"hello"[..], &b" wo"[..], &b"rld"[..])
+        );
+        assert_eq!(first.as_ptr(), start);
+        assert_eq!(second.as_ptr(), start.wrapping_add(5));
+        assert_eq!(rest.as_ptr(), start.wrapping_add(8));
+        assert!(read_bytes(&mut reader, 100).is_empty());
+    }
+
+    #[test]
+    fn reads_continue_after_a_chunk_is_taken() {
+        let mut reader = Reader::new(&b"hello world"[..]);
+        assert_eq!(&read_bytes(&mut reader, 2)[..], b"he");
+        assert_eq!(reader.size_hint(), (9, Some(9)));
+
+        let mut buf = [0; 4];
+        let n = block_on(poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf))).unwrap();
+        assert_eq!(&buf[..n], b"llo ");
+        Pin::new(&mut reader).consume(1);
+
+        let (inner, taken) = reader.into_parts();
+        assert!(inner.is_empty());
+        assert_eq!(&taken[..], b"orld");
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{AsyncRead, AsyncWrite, Classify, ErrorKind};
+use _futures::ready;
+use core::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use pin_project_lite::pin_project;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractViolation {
+    ReadOverflow { len: usize, reported: usize },
+    WriteOverflow { len: usize, reported: usize },
+    DataAfterEof { reported: usize },
+    WriteAfterClose,
+}
+
+impl fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractViolation::ReadOverflow { len, reported } => write!(
+                f,
+                "poll_read reported {} bytes for a buffer of {} bytes",
+                reported, len
+            ),
+            ContractViolation::WriteOverflow { len, reported } => write!(
+                f,
+                "poll_write reported {} bytes for a buffer of {} bytes",
+                reported, len
+            ),
+            ContractViolation::DataAfterEof { reported } => write!(
+                f,
+                "poll_read returned {} bytes after signalling EOF",
+                reported
+            ),
+            ContractViolation::WriteAfterClose => f.write_str("poll_write called after poll_close"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ContractViolation {}
+
+impl Classify for ContractViolation {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::InvalidData
+    }
+}
+
+#[derive(Debug)]
+pub enum CheckedError<T> {
+    Inner(T),
+    Violation(ContractViolation),
+}
+
+impl<T: fmt::Display> fmt::Display for CheckedError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckedError::Inner(e) => e.fmt(f),
+            CheckedError::Violation(violation) => violation.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::error::Error> std::error::Error for CheckedError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckedError::Inner(e) => e.source(),
+            CheckedError::Violation(_) => None,
+        }
+    }
+}
+
+impl<T: Classify> Classify for CheckedError<T> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            CheckedError::Inner(e) => e.kind(),
+            CheckedError::Violation(violation) => violation.kind(),
+        }
+    }
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Checked<T> {
+        #[pin]
+        inner: T,
+        eof: bool,
+        closed: bool,
+    }
+}
+
+impl<T> Checked<T> {
+    pub fn new(inner: T) -> Self {
+        Checked {
+            inner,
+            eof: false,
+            closed: false,
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for Checked<T> {
+    type Error = CheckedError<T::Error>;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.project();
+        let len = buf.len();
+
+        let n = ready!(me.inner.poll_read(cx, buf)).map_err(CheckedError::Inner)?;
+
+        if n > len {
+            return Poll::Ready(Err(CheckedError::Violation(
+                ContractViolation::ReadOverflow { len, reported: n },
+            )));
+        }
+        if *me.eof && n > 0 {
+            return Poll::Ready(Err(CheckedError::Violation(
+                ContractViolation::DataAfterEof { reported: n },
+            )));
+        }
+        if n == 0 && len > 0 {
+            *me.eof = true;
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.project()
+            .inner
+            .poll_read_ready(cx)
+            .map_err(CheckedError::Inner)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Checked<T> {
+    type WriteError = CheckedError<T::WriteError>;
+    type FlushError = T::FlushError;
+    type CloseError = T::CloseError;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        let me = self.project();
+
+        if *me.closed {
+            return Poll::Ready(Err(CheckedError::Violation(
+                ContractViolation::WriteAfterClose,
+            )));
+        }
+
+        let n = ready!(me.inner.poll_write(cx, buf)).map_err(CheckedError::Inner)?;
+
+        if n > buf.len() {
+            return Poll::Ready(Err(CheckedError::Violation(
+                ContractViolation::WriteOverflow {
+                    len: buf.len(),
+                    reported: n,
+                },
+            )));
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        let me = self.project();
+        ready!(me.inner.poll_close(cx))?;
+        *me.closed = true;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        let me = self.project();
+
+        if *me.closed {
+            return Poll::Ready(Err(CheckedError::Violation(
+                ContractViolation::WriteAfterClose,
+            )));
+        }
+
+        me.inner.poll_write_ready(cx).map_err(CheckedError::Inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt, AsyncWriteExt};
+    use core::future::poll_fn;
+    use void::Void;
+
+    /// Reports a scripted byte count for each read, whatever the buffer size.
+    struct Liar<'a>(&'a [usize]);
+
+    impl AsyncRead for Liar<'_> {
+        type Error = Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let (&n, rest) = self.0.split_first().expect("read past the script");
+            self.0 = rest;
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    /// Claims to have written one byte more than it was given.
+    struct Overwriter;
+
+    impl AsyncWrite for Overwriter {
+        type WriteError = Void;
+        type FlushError = Void;
+        type CloseError = Void;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            Poll::Ready(Ok(buf.len() + 1))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn read<T: AsyncRead + Unpin>(
+        reader: &mut Checked<T>,
+        len: usize,
+    ) -> Result<usize, CheckedError<T::Error>> {
+        let mut buf = [0; 8];
+        block_on(reader.read(&mut buf[..len]))
+    }
+
+    #[test]
+    fn read_overflow() {
+        let mut reader = Checked::new(Liar(&[9]));
+        match read(&mut reader, 4) {
+            Err(CheckedError::Violation(violation)) => assert_eq!(
+                violation,
+                ContractViolation::ReadOverflow {
+                    len: 4,
+                    reported: 9
+                }
+            ),
+            other => panic!("expected a violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn data_after_eof() {
+        let mut reader = Checked::new(Liar(&[2, 0, 3]));
+        assert_eq!(read(&mut reader, 4).unwrap(), 2);
+        assert_eq!(read(&mut reader, 4).unwrap(), 0);
+        match read(&mut reader, 4) {
+            Err(CheckedError::Violation(violation)) => {
+                assert_eq!(violation, ContractViolation::DataAfterEof { reported: 3 })
+            }
+            other => panic!("expected a violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_read_is_not_eof() {
+        let mut reader = Checked::new(Liar(&[0, 3]));
+        assert_eq!(read(&mut reader, 0).unwrap(), 0);
+        assert_eq!(read(&mut reader, 4).unwrap(), 3);
+    }
+
+    #[test]
+    fn write_overflow() {
+        let mut writer = Checked::new(Overwriter);
+        match block_on(writer.write(b"abc")) {
+            Err(CheckedError::Violation(violation)) => assert_eq!(
+                violation,
+                ContractViolation::WriteOverflow {
+                    len: 3,
+                    reported: 4
+                }
+            ),
+            other => panic!("expected a violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn write_after_close() {
+        let mut writer = Checked::new(crate::Sink);
+        block_on(writer.close()).unwrap();
+        match block_on(writer.write(b"abc")) {
+            Err(CheckedError::Violation(violation)) => {
+                assert_eq!(violation, ContractViolation::WriteAfterClose)
+            }
+            other => panic!("expected a violation, got {:?}", other),
+        }
+        match block_on(poll_fn(|cx| Pin::new(&mut writer).poll_write_ready(cx))) {
+            Err(CheckedError::Violation(ContractViolation::WriteAfterClose)) => {}
+            other => panic!("expected a violation, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use crate::{AsyncRead, AsyncWrite};
+use _futures::task::{waker, ArcWake};
+use core::{
+    cmp, fmt,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+};
+use std::{
+    sync::Arc,
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Read,
+    Write,
+    Flush,
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    ReadOverflow { len: usize, reported: usize },
+    WriteOverflow { len: usize, reported: usize },
+    PendingWithoutWake(Op),
+    NoProgress(Op),
+    DataAfterEof { reported: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::ReadOverflow { len, reported } => write!(
+                f,
+                "poll_read reported {} bytes for a buffer of {} bytes",
+                reported, len
+            ),
+            Violation::WriteOverflow { len, reported } => write!(
+                f,
+                "poll_write reported {} bytes for a buffer of {} bytes",
+                reported, len
+            ),
+            Violation::PendingWithoutWake(op) => {
+                write!(f, "{:?} returned Pending without ever waking the task", op)
+            }
+            Violation::NoProgress(op) => write!(
+                f,
+                "{:?} kept waking the task but never completed before the timeout",
+                op
+            ),
+            Violation::DataAfterEof { reported } => write!(
+                f,
+                "poll_read returned {} bytes after signalling EOF",
+                reported
+            ),
+        }
+    }
+}
+
+struct Signal {
+    woken: AtomicBool,
+    thread: Thread,
+}
+
+impl ArcWake for Signal {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.woken.store(true, Ordering::SeqCst);
+        arc_self.thread.unpark();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Harness {
+    timeout: Duration,
+    buffer_sizes: Vec<usize>,
+    eof_probes: usize,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Harness {
+            timeout: Duration::from_secs(1),
+            buffer_sizes: vec![1, 7, 64, 4096],
+            eof_probes: 2,
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn buffer_sizes(mut self, sizes: &[usize]) -> Self {
+        assert!(
+            sizes.iter().any(|&size| size > 0),
+            "at least one buffer size must be non-zero"
+        );
+        self.buffer_sizes = sizes.iter().copied().filter(|&size| size > 0).collect();
+        self
+    }
+
+    pub fn eof_probes(mut self, probes: usize) -> Self {
+        self.eof_probes = probes;
+        self
+    }
+
+    fn drive<T>(
+        &self,
+        op: Op,
+        violations: &mut Vec<Violation>,
+        mut poll: impl FnMut(&mut Context) -> Poll<T>,
+    ) -> Option<T> {
+        // Bounds the whole operation, so an implementation that wakes itself and stays
+        // pending forever is reported instead of hanging the harness.
+        let overall = Instant::now() + self.timeout;
+        loop {
+            let signal = Arc::new(Signal {
+                woken: AtomicBool::new(false),
+                thread: thread::current(),
+            });
+            let waker = waker(signal.clone());
+            let mut cx = Context::from_waker(&waker);
+
+            if let Poll::Ready(output) = poll(&mut cx) {
+                return Some(output);
+            }
+
+            let deadline = Instant::now() + self.timeout;
+            while !signal.woken.load(Ordering::SeqCst) {
+                let now = Instant::now();
+                if now >= deadline {
+                    violations.push(Violation::PendingWithoutWake(op));
+                    return None;
+                }
+                thread::park_timeout(deadline - now);
+            }
+
+            if Instant::now() >= overall {
+                violations.push(Violation::NoProgress(op));
+                return None;
+            }
+        }
+    }
+
+    pub fn check_read<R>(&self, mut reader: Pin<&mut R>) -> ReadReport<R::Error>
+    where
+        R: AsyncRead + ?Sized,
+    {
+        let mut report = ReadReport {
+            data: Vec::new(),
+            error: None,
+            violations: Vec::new(),
+        };
+        let max = self.buffer_sizes.iter().copied().max().unwrap_or(0);
+        let mut buf = vec![0u8; max];
+
+        let mut empty = [0u8; 0];
+        match self.drive(Op::Read, &mut report.violations, |cx| {
+            reader.as_mut().poll_read(cx, &mut empty)
+        }) {
+            None => return report,
+            Some(Err(e)) => {
+                report.error = Some(e);
+                return report;
+            }
+            Some(Ok(0)) => {}
+            Some(Ok(n)) => report.violations.push(Violation::ReadOverflow {
+                len: 0,
+                reported: n,
+            }),
+        }
+
+        let mut sizes = self.buffer_sizes.iter().copied().cycle();
+        let mut eof = false;
+        let mut probes = 0;
+
+        while !eof || probes < self.eof_probes {
+            let len = sizes.next().unwrap_or(1);
+            let chunk = &mut buf[..len];
+            let n = match self.drive(Op::Read, &mut report.violations, |cx| {
+                reader.as_mut().poll_read(cx, chunk)
+            }) {
+                None => break,
+                Some(Err(e)) => {
+                    report.error = Some(e);
+                    break;
+                }
+                Some(Ok(n)) => n,
+            };
+
+            if n > len {
+                report
+                    .violations
+                    .push(Violation::ReadOverflow { len, reported: n });
+            }
+            let n = cmp::min(n, len);
+
+            if eof {
+                probes += 1;
+                if n > 0 {
+                    report
+                        .violations
+                        .push(Violation::DataAfterEof { reported: n });
+                }
+            } else if n == 0 {
+                eof = true;
+            } else {
+                report.data.extend_from_slice(&buf[..n]);
+            }
+        }
+
+        report
+    }
+
+    pub fn check_write<W>(&self, mut writer: Pin<&mut W>, data: &[u8]) -> WriteReport<W>
+    where
+        W: AsyncWrite + ?Sized,
+    {
+        let mut report = WriteReport {
+            written: 0,
+            write_zero: false,
+            write_error: None,
+            flush_error: None,
+            close_error: None,
+            violations: Vec::new(),
+        };
+        let mut sizes = self.buffer_sizes.iter().copied().cycle();
+
+        while report.written < data.len() {
+            let len = cmp::min(sizes.next().unwrap_or(1), data.len() - report.written);
+            let chunk = &data[report.written..report.written + len];
+            let n = match self.drive(Op::Write, &mut report.violations, |cx| {
+                writer.as_mut().poll_write(cx, chunk)
+            }) {
+                None => return report,
+                Some(Err(e)) => {
+                    report.write_error = Some(e);
+                    return report;
+                }
+                Some(Ok(0)) => {
+                    report.write_zero = true;
+                    break;
+                }
+                Some(Ok(n)) => n,
+            };
+
+            if n > len {
+                report
+                    .violations
+                    .push(Violation::WriteOverflow { len, reported: n });
+            }
+            report.written += cmp::min(n, len);
+        }
+
+        match self.drive(Op::Flush, &mut report.violations, |cx| {
+            writer.as_mut().poll_flush(cx)
+        }) {
+            None => return report,
+            Some(Err(e)) => {
+                report.flush_error = Some(e);
+                return report;
+            }
+            Some(Ok(())) => {}
+        }
+
+        if let Some(Err(e)) = self.drive(Op::Close, &mut report.violations, |cx| {
+            writer.as_mut().poll_close(cx)
+        }) {
+            report.close_error = Some(e);
+        }
+
+        report
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Harness::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct ReadReport<E> {
+    pub data: Vec<u8>,
+    pub error: Option<E>,
+    pub violations: Vec<Violation>,
+}
+
+impl<E> ReadReport<E> {
+    pub fn is_conformant(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn assert_conformant(&self) {
+        assert_conformant(&self.violations)
+    }
+}
+
+pub struct WriteReport<W: AsyncWrite + ?Sized> {
+    pub written: usize,
+    /// Set if the writer accepted no bytes of a non-empty buffer before all of the data was
+    /// written.
+    pub write_zero: bool,
+    pub write_error: Option<W::WriteError>,
+    pub flush_error: Option<W::FlushError>,
+    pub close_error: Option<W::CloseError>,
+    pub violations: Vec<Violation>,
+}
+
+impl<W: AsyncWrite + ?Sized> WriteReport<W> {
+    pub fn is_conformant(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn assert_conformant(&self) {
+        assert_conformant(&self.violations)
+    }
+}
+
+impl<W: AsyncWrite + ?Sized> fmt::Debug for WriteReport<W>
+where
+    W::WriteError: fmt::Debug,
+    W::FlushError: fmt::Debug,
+    W::CloseError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteReport")
+            .field("written", &self.written)
+            .field("write_zero", &self.write_zero)
+            .field("write_error", &self.write_error)
+            .field("flush_error", &self.flush_error)
+            .field("close_error", &self.close_error)
+            .field("violations", &self.violations)
+            .finish()
+    }
+}
+
+fn assert_conformant(violations: &[Violation]) {
+    if !violations.is_empty() {
+        let list = violations
+            .iter()
+            .map(|violation| format!("  - {}", violation))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!("implementation violated the I/O contract:\n{}", list);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Spinning;
+
+    impl AsyncRead for Spinning {
+        type Error = void::Void;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn self_waking_reader_is_reported() {
+        let report = Harness::new()
+            .timeout(Duration::from_millis(20))
+            .check_read(Pin::new(&mut Spinning));
+        assert_eq!(report.violations, [Violation::NoProgress(Op::Read)]);
+    }
+
+    /// Accepts up to ten bytes, then returns `Ok(0)`.
+    struct Full(usize);
+
+    impl AsyncWrite for Full {
+        type WriteError = void::Void;
+        type FlushError = void::Void;
+        type CloseError = void::Void;
+
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            let n = cmp::min(buf.len(), 10 - self.0);
+            self.0 += n;
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn write_zero_is_reported() {
+        let report = Harness::new().check_write(Pin::new(&mut Full(0)), &[1; 16]);
+        assert_eq!(report.written, 10);
+        assert!(report.write_zero);
+        assert!(report.is_conformant());
+    }
+}
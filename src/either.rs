@@ -0,0 +1,309 @@
+use crate::{AsyncBufRead, AsyncRead, AsyncWrite, BorrowedCursor, Classify, ErrorKind, Split};
+use _futures::future::Either;
+use bytes::Bytes;
+use core::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[derive(Debug)]
+pub enum EitherError<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<T> EitherError<T, T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            EitherError::Left(e) => e,
+            EitherError::Right(e) => e,
+        }
+    }
+}
+
+impl<L: fmt::Display, R: fmt::Display> fmt::Display for EitherError<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EitherError::Left(e) => e.fmt(f),
+            EitherError::Right(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<L: std::error::Error, R: std::error::Error> std::error::Error for EitherError<L, R> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EitherError::Left(e) => e.source(),
+            EitherError::Right(e) => e.source(),
+        }
+    }
+}
+
+impl<L: Classify, R: Classify> Classify for EitherError<L, R> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            EitherError::Left(e) => e.kind(),
+            EitherError::Right(e) => e.kind(),
+        }
+    }
+}
+
+impl<L, R> AsyncRead for Either<L, R>
+where
+    L: AsyncRead,
+    R: AsyncRead,
+{
+    type Error = EitherError<L::Error, R::Error>;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_read(cx, buf).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_read(cx, buf).map_err(EitherError::Right),
+        }
+    }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_read_uninit(cx, buf).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_read_uninit(cx, buf).map_err(EitherError::Right),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Either::Left(x) => x.size_hint(),
+            Either::Right(x) => x.size_hint(),
+        }
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_read_ready(cx).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_read_ready(cx).map_err(EitherError::Right),
+        }
+    }
+}
+
+impl<L, R> AsyncBufRead for Either<L, R>
+where
+    L: AsyncBufRead,
+    R: AsyncBufRead,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<&[u8], Self::Error>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_fill_buf(cx).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_fill_buf(cx).map_err(EitherError::Right),
+        }
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.consume(amt),
+            Either::Right(x) => x.consume(amt),
+        }
+    }
+
+    fn poll_read_bytes(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        max: usize,
+    ) -> Poll<Result<Bytes, Self::Error>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_read_bytes(cx, max).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_read_bytes(cx, max).map_err(EitherError::Right),
+        }
+    }
+}
+
+impl<L, R> AsyncWrite for Either<L, R>
+where
+    L: AsyncWrite,
+    R: AsyncWrite,
+{
+    type WriteError = EitherError<L::WriteError, R::WriteError>;
+    type FlushError = EitherError<L::FlushError, R::FlushError>;
+    type CloseError = EitherError<L::CloseError, R::CloseError>;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_write(cx, buf).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_write(cx, buf).map_err(EitherError::Right),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_flush(cx).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_flush(cx).map_err(EitherError::Right),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_close(cx).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_close(cx).map_err(EitherError::Right),
+        }
+    }
+
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        match self.as_pin_mut() {
+            Either::Left(x) => x.poll_write_ready(cx).map_err(EitherError::Left),
+            Either::Right(x) => x.poll_write_ready(cx).map_err(EitherError::Right),
+        }
+    }
+}
+
+impl<L, R> Split for Either<L, R>
+where
+    L: Split,
+    R: Split,
+{
+    type ReadHalf = Either<L::ReadHalf, R::ReadHalf>;
+    type WriteHalf = Either<L::WriteHalf, R::WriteHalf>;
+
+    fn split(self) -> (Self::ReadHalf, Self::WriteHalf) {
+        match self {
+            Either::Left(x) => {
+                let (read, write) = x.split();
+                (Either::Left(read), Either::Left(write))
+            }
+            Either::Right(x) => {
+                let (read, write) = x.split();
+                (Either::Right(read), Either::Right(write))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+    /// Fills reads with its byte and accepts every write, or fails everything with its error.
+    struct Side(Result<u8, &'static str>);
+
+    impl AsyncRead for Side {
+        type Error = &'static str;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let byte = self.0?;
+            buf.iter_mut().for_each(|b| *b = byte);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.0.unwrap_or(0).into(), None)
+        }
+    }
+
+    impl AsyncWrite for Side {
+        type WriteError = &'static str;
+        type FlushError = &'static str;
+        type CloseError = &'static str;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            self.0?;
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(self.0.map(drop))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(self.0.map(drop))
+        }
+    }
+
+    type Both = Either<Side, Side>;
+
+    #[test]
+    fn reads_dispatch_to_the_active_side() {
+        let mut buf = [0; 3];
+        let mut left: Both = Either::Left(Side(Ok(1)));
+        assert_eq!(block_on(left.read(&mut buf)).unwrap(), 3);
+        assert_eq!(buf, [1; 3]);
+        assert_eq!(left.size_hint(), (1, None));
+
+        let mut right: Both = Either::Right(Side(Ok(2)));
+        block_on(right.read_exact(&mut buf)).unwrap();
+        assert_eq!(buf, [2; 3]);
+        assert_eq!(right.size_hint(), (2, None));
+    }
+
+    #[test]
+    fn errors_are_tagged_with_their_side() {
+        let mut left: Both = Either::Left(Side(Err("left")));
+        let mut right: Both = Either::Right(Side(Err("right")));
+
+        let err = block_on(left.read(&mut [0; 1])).unwrap_err();
+        assert!(matches!(err, EitherError::Left("left")));
+        assert_eq!(err.into_inner(), "left");
+        let err = block_on(right.read(&mut [0; 1])).unwrap_err();
+        assert!(matches!(err, EitherError::Right("right")));
+        assert_eq!(err.into_inner(), "right");
+
+        let err = block_on(left.write(b"x")).unwrap_err();
+        assert_eq!(err.into_inner(), "left");
+        let err = block_on(right.flush()).unwrap_err();
+        assert!(matches!(err, EitherError::Right("right")));
+        let err = block_on(right.close()).unwrap_err();
+        assert_eq!(err.into_inner(), "right");
+    }
+
+    #[test]
+    fn writes_dispatch_to_the_active_side() {
+        let mut left: Both = Either::Left(Side(Ok(0)));
+        assert_eq!(block_on(left.write(b"abc")).unwrap(), 3);
+        block_on(left.flush()).unwrap();
+        block_on(left.close()).unwrap();
+
+        let mut right: Both = Either::Right(Side(Ok(0)));
+        block_on(right.write_all(b"abc")).unwrap();
+    }
+
+    fn fill_buf<R: AsyncBufRead + Unpin>(reader: &mut R) -> &[u8] {
+        let mut cx = Context::from_waker(_futures::task::noop_waker_ref());
+        match Pin::new(reader).poll_fill_buf(&mut cx) {
+            Poll::Ready(Ok(buf)) => buf,
+            _ => panic!("fill_buf did not complete"),
+        }
+    }
+
+    #[test]
+    fn buffered_reads_dispatch_to_the_active_side() {
+        let mut left: Either<Bytes, Bytes> = Either::Left(Bytes::from_static(b"left"));
+        let mut right: Either<Bytes, Bytes> = Either::Right(Bytes::from_static(b"right"));
+        assert_eq!(fill_buf(&mut left), b"left");
+        left.consume(2);
+        assert_eq!(fill_buf(&mut left), b"ft");
+        assert_eq!(block_on(right.read_chunk(3)).unwrap(), "rig");
+        assert_eq!(fill_buf(&mut right), b"ht");
+    }
+}
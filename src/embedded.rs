@@ -0,0 +1,648 @@
+use crate::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Classify, ErrorKind};
+use _embedded_io::{Error as EError, ErrorKind as EErrorKind, ErrorType};
+use _embedded_io_async::{Read as ERead, Write as EWrite};
+use core::fmt;
+
+fn embedded_kind(kind: ErrorKind) -> EErrorKind {
+    match kind {
+        ErrorKind::WriteZero => EErrorKind::WriteZero,
+        ErrorKind::Interrupted => EErrorKind::Interrupted,
+        ErrorKind::InvalidInput => EErrorKind::InvalidInput,
+        ErrorKind::InvalidData => EErrorKind::InvalidData,
+        ErrorKind::UnexpectedEof | ErrorKind::WouldBlock | ErrorKind::Other => EErrorKind::Other,
+    }
+}
+
+#[derive(Debug)]
+pub struct ReadError<E>(pub E);
+
+impl<E: fmt::Debug + Classify> EError for ReadError<E> {
+    fn kind(&self) -> EErrorKind {
+        embedded_kind(self.0.kind())
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error> std::error::Error for ReadError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl<E: Classify> Classify for ReadError<E> {
+    fn kind(&self) -> ErrorKind {
+        self.0.kind()
+    }
+}
+
+#[derive(Debug)]
+pub enum WriteError<W, F> {
+    Write(W),
+    Flush(F),
+}
+
+impl<W: fmt::Display, F: fmt::Display> fmt::Display for WriteError<W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Write(e) => e.fmt(f),
+            WriteError::Flush(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::error::Error, F: std::error::Error> std::error::Error for WriteError<W, F> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteError::Write(e) => e.source(),
+            WriteError::Flush(e) => e.source(),
+        }
+    }
+}
+
+impl<W: Classify, F: Classify> Classify for WriteError<W, F> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            WriteError::Write(e) => e.kind(),
+            WriteError::Flush(e) => e.kind(),
+        }
+    }
+}
+
+impl<W, F> EError for WriteError<W, F>
+where
+    W: fmt::Debug + Classify,
+    F: fmt::Debug + Classify,
+{
+    fn kind(&self) -> EErrorKind {
+        match self {
+            WriteError::Write(e) => embedded_kind(e.kind()),
+            WriteError::Flush(e) => embedded_kind(e.kind()),
+        }
+    }
+}
+
+/// Exposes an [`AsyncRead`] as an `embedded-io-async` reader.
+///
+/// Works without `alloc` and never boxes; this is the direction to use on no-alloc targets.
+#[derive(Debug)]
+pub struct Reader<T>(T);
+
+impl<T> Reader<T> {
+    pub fn new(input: T) -> Self {
+        Reader(input)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: AsyncRead> ErrorType for Reader<T>
+where
+    T::Error: fmt::Debug + Classify,
+{
+    type Error = ReadError<T::Error>;
+}
+
+impl<T: AsyncRead + Unpin> ERead for Reader<T>
+where
+    T::Error: fmt::Debug + Classify,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        AsyncReadExt::read(&mut self.0, buf)
+            .await
+            .map_err(ReadError)
+    }
+}
+
+/// Exposes an [`AsyncWrite`] as an `embedded-io-async` writer.
+///
+/// Works without `alloc` and never boxes; this is the direction to use on no-alloc targets.
+#[derive(Debug)]
+pub struct Writer<T>(T);
+
+impl<T> Writer<T> {
+    pub fn new(input: T) -> Self {
+        Writer(input)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: AsyncWrite> ErrorType for Writer<T>
+where
+    T::WriteError: fmt::Debug + Classify,
+    T::FlushError: fmt::Debug + Classify,
+{
+    type Error = WriteError<T::WriteError, T::FlushError>;
+}
+
+impl<T: AsyncWrite + Unpin> EWrite for Writer<T>
+where
+    T::WriteError: fmt::Debug + Classify,
+    T::FlushError: fmt::Debug + Classify,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        AsyncWriteExt::write(&mut self.0, buf)
+            .await
+            .map_err(WriteError::Write)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        AsyncWriteExt::flush(&mut self.0)
+            .await
+            .map_err(WriteError::Flush)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod compat {
+    use super::*;
+    use alloc::{boxed::Box, vec::Vec};
+    use core::{
+        cmp,
+        future::Future,
+        mem,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    type Operation<'a, T, E> = Pin<Box<dyn Future<Output = (T, Vec<u8>, Result<usize, E>)> + 'a>>;
+
+    enum Kind {
+        Read,
+        Write,
+        Flush,
+    }
+
+    enum State<'a, T, E> {
+        Idle(T),
+        Busy(Kind, Operation<'a, T, E>),
+        Empty,
+    }
+
+    /// Exposes an `embedded-io-async` reader or writer through [`AsyncRead`] and
+    /// [`AsyncWrite`].
+    ///
+    /// The HAL traits only offer `async fn`s, whose futures cannot be named or stored
+    /// without boxing. Each operation is therefore boxed and owns the inner value while it
+    /// runs, so this adapter is only available with the `alloc` feature and allocates once per
+    /// operation. Drivers may borrow their peripherals; the adapter lives no longer than
+    /// the borrow. On targets without an allocator only the other direction, [`Reader`] and
+    /// [`Writer`], is available.
+    ///
+    /// Once `poll_write` has returned `Pending` the data is already submitted. Retry it with
+    /// the same buffer to learn how much was written; a `poll_flush` or `poll_close` in between
+    /// waits for the write and treats it as committed.
+    pub struct Compat<'a, T: ErrorType + 'a> {
+        state: State<'a, T, T::Error>,
+        read_buf: Vec<u8>,
+        read_pos: usize,
+        read: Option<Result<usize, T::Error>>,
+        write_buf: Vec<u8>,
+        written: Option<Result<usize, T::Error>>,
+        flushed: Option<Result<(), T::Error>>,
+    }
+
+    impl<'a, T: ErrorType + 'a> Unpin for Compat<'a, T> {}
+
+    impl<'a, T: ErrorType + 'a> Compat<'a, T> {
+        pub fn new(input: T) -> Self {
+            Compat {
+                state: State::Idle(input),
+                read_buf: Vec::new(),
+                read_pos: 0,
+                read: None,
+                write_buf: Vec::new(),
+                written: None,
+                flushed: None,
+            }
+        }
+
+        pub fn get_ref(&self) -> Option<&T> {
+            match &self.state {
+                State::Idle(inner) => Some(inner),
+                _ => None,
+            }
+        }
+
+        pub fn get_mut(&mut self) -> Option<&mut T> {
+            match &mut self.state {
+                State::Idle(inner) => Some(inner),
+                _ => None,
+            }
+        }
+
+        pub fn into_inner(self) -> Option<T> {
+            match self.state {
+                State::Idle(inner) => Some(inner),
+                _ => None,
+            }
+        }
+
+        fn drive(&mut self, cx: &mut Context) -> Poll<()> {
+            if let State::Busy(kind, operation) = &mut self.state {
+                let (inner, buf, result) = match operation.as_mut().poll(cx) {
+                    Poll::Ready(output) => output,
+                    Poll::Pending => return Poll::Pending,
+                };
+                match kind {
+                    Kind::Read => {
+                        self.read_buf = buf;
+                        self.read_pos = 0;
+                        self.read = Some(result);
+                    }
+                    Kind::Write => {
+                        self.write_buf = buf;
+                        self.written = Some(result);
+                    }
+                    Kind::Flush => {
+                        self.flushed = Some(result.map(|_| ()));
+                    }
+                }
+                self.state = State::Idle(inner);
+            }
+            Poll::Ready(())
+        }
+
+        fn start(
+            &mut self,
+            kind: Kind,
+            start: impl FnOnce(T, Vec<u8>) -> Operation<'a, T, T::Error>,
+        ) {
+            if let Kind::Flush = kind {
+                // A write that a flush had to wait for is committed; its count must not be
+                // handed to an unrelated later `poll_write`. Errors are kept.
+                if let Some(Ok(_)) = self.written {
+                    self.written = None;
+                }
+            }
+            let buf = match kind {
+                Kind::Read => mem::take(&mut self.read_buf),
+                Kind::Write => mem::take(&mut self.write_buf),
+                Kind::Flush => Vec::new(),
+            };
+            if let State::Idle(inner) = mem::replace(&mut self.state, State::Empty) {
+                self.state = State::Busy(kind, start(inner, buf));
+            }
+        }
+    }
+
+    impl<'a, T> AsyncRead for Compat<'a, T>
+    where
+        T: ERead + 'a,
+        T::Error: 'a,
+    {
+        type Error = T::Error;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let me = self.get_mut();
+
+            loop {
+                match me.read.take() {
+                    Some(Ok(n)) => {
+                        let len = cmp::min(n - me.read_pos, buf.len());
+                        buf[..len].copy_from_slice(&me.read_buf[me.read_pos..me.read_pos + len]);
+                        me.read_pos += len;
+                        if me.read_pos < n {
+                            me.read = Some(Ok(n));
+                        }
+                        return Poll::Ready(Ok(len));
+                    }
+                    Some(Err(e)) => return Poll::Ready(Err(e)),
+                    None => {}
+                }
+
+                if buf.is_empty() {
+                    return Poll::Ready(Ok(0));
+                }
+
+                match me.state {
+                    State::Busy(..) => {
+                        if me.drive(cx).is_pending() {
+                            return Poll::Pending;
+                        }
+                    }
+                    State::Idle(_) => {
+                        let len = buf.len();
+                        me.start(Kind::Read, |mut inner, mut buf| {
+                            buf.resize(len, 0);
+                            Box::pin(async move {
+                                let result = inner.read(&mut buf).await;
+                                (inner, buf, result)
+                            })
+                        });
+                    }
+                    State::Empty => unreachable!(),
+                }
+            }
+        }
+    }
+
+    impl<'a, T> AsyncWrite for Compat<'a, T>
+    where
+        T: EWrite + 'a,
+        T::Error: 'a,
+    {
+        type WriteError = T::Error;
+        type FlushError = T::Error;
+        type CloseError = T::Error;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            let me = self.get_mut();
+
+            loop {
+                if let Some(result) = me.written.take() {
+                    return Poll::Ready(result.map(|n| cmp::min(n, buf.len())));
+                }
+
+                match me.state {
+                    State::Busy(..) => {
+                        if me.drive(cx).is_pending() {
+                            return Poll::Pending;
+                        }
+                    }
+                    State::Idle(_) => me.start(Kind::Write, |mut inner, mut data| {
+                        data.clear();
+                        data.extend_from_slice(buf);
+                        Box::pin(async move {
+                            let result = inner.write(&data).await;
+                            (inner, data, result)
+                        })
+                    }),
+                    State::Empty => unreachable!(),
+                }
+            }
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::FlushError>> {
+            let me = self.get_mut();
+
+            loop {
+                if let Some(result) = me.flushed.take() {
+                    return Poll::Ready(result);
+                }
+
+                match me.state {
+                    State::Busy(..) => {
+                        if me.drive(cx).is_pending() {
+                            return Poll::Pending;
+                        }
+                    }
+                    State::Idle(_) => me.start(Kind::Flush, |mut inner, buf| {
+                        Box::pin(async move {
+                            let result = inner.flush().await.map(|_| 0);
+                            (inner, buf, result)
+                        })
+                    }),
+                    State::Empty => unreachable!(),
+                }
+            }
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::CloseError>> {
+            self.poll_flush(cx)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use compat::Compat;
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::block_on;
+    use alloc::vec::Vec;
+    use core::{
+        cmp,
+        future::poll_fn,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Fault(EErrorKind);
+
+    impl EError for Fault {
+        fn kind(&self) -> EErrorKind {
+            self.0
+        }
+    }
+
+    /// Completes every operation on its second poll.
+    #[derive(Default)]
+    struct Slow {
+        input: Vec<u8>,
+        data: Vec<u8>,
+        fail: Option<EErrorKind>,
+    }
+
+    async fn yield_once() {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    impl ErrorType for Slow {
+        type Error = Fault;
+    }
+
+    impl ERead for Slow {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Fault> {
+            yield_once().await;
+            if let Some(kind) = self.fail {
+                return Err(Fault(kind));
+            }
+            let n = cmp::min(buf.len(), self.input.len());
+            buf[..n].copy_from_slice(&self.input[..n]);
+            self.input.drain(..n);
+            Ok(n)
+        }
+    }
+
+    impl EWrite for Slow {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Fault> {
+            yield_once().await;
+            if let Some(kind) = self.fail {
+                return Err(Fault(kind));
+            }
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        async fn flush(&mut self) -> Result<(), Fault> {
+            yield_once().await;
+            match self.fail {
+                Some(kind) => Err(Fault(kind)),
+                None => Ok(()),
+            }
+        }
+    }
+
+    fn noop_cx() -> Context<'static> {
+        Context::from_waker(_futures::task::noop_waker_ref())
+    }
+
+    #[test]
+    fn read_waits_for_pending_driver() {
+        let mut r = Compat::new(Slow {
+            input: b"hello world".to_vec(),
+            ..Slow::default()
+        });
+        let mut buf = [0; 4];
+        assert!(Pin::new(&mut r)
+            .poll_read(&mut noop_cx(), &mut buf)
+            .is_pending());
+
+        let mut out = Vec::new();
+        block_on(r.read_to_end(&mut out)).unwrap();
+        assert_eq!(out, b"hello world");
+    }
+
+    #[test]
+    fn flush_does_not_leak_write_result() {
+        let mut w = Compat::new(Slow::default());
+        assert!(Pin::new(&mut w)
+            .poll_write(&mut noop_cx(), b"hello world")
+            .is_pending());
+        block_on(poll_fn(|cx| Pin::new(&mut w).poll_flush(cx))).unwrap();
+
+        let n = block_on(poll_fn(|cx| Pin::new(&mut w).poll_write(cx, b"ab")));
+        assert_eq!(n.unwrap(), 2);
+        assert_eq!(w.into_inner().unwrap().data, b"hello worldab");
+    }
+
+    #[test]
+    fn borrowed_driver() {
+        let mut driver = Slow::default();
+        {
+            let mut w = Compat::new(&mut driver);
+            block_on(w.write_all(b"abc")).unwrap();
+            block_on(w.flush()).unwrap();
+        }
+        assert_eq!(driver.data, b"abc");
+    }
+
+    #[test]
+    fn driver_errors_reach_the_caller() {
+        let mut c = Compat::new(Slow {
+            input: b"unread".to_vec(),
+            fail: Some(EErrorKind::TimedOut),
+            ..Slow::default()
+        });
+        let mut buf = [0; 4];
+        let err = block_on(c.read(&mut buf)).unwrap_err();
+        assert_eq!(err, Fault(EErrorKind::TimedOut));
+        let err = block_on(c.write(b"x")).unwrap_err();
+        assert_eq!(err, Fault(EErrorKind::TimedOut));
+        let err = block_on(c.flush()).unwrap_err();
+        assert_eq!(err, Fault(EErrorKind::TimedOut));
+    }
+
+    #[derive(Debug)]
+    struct Boom(ErrorKind);
+
+    impl Classify for Boom {
+        fn kind(&self) -> ErrorKind {
+            self.0
+        }
+    }
+
+    /// Fails reads and flushes, accepts writes.
+    struct Failing;
+
+    impl AsyncRead for Failing {
+        type Error = Boom;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Boom>> {
+            Poll::Ready(Err(Boom(ErrorKind::InvalidData)))
+        }
+    }
+
+    impl AsyncWrite for Failing {
+        type WriteError = Boom;
+        type FlushError = Boom;
+        type CloseError = Boom;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Boom>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Boom>> {
+            Poll::Ready(Err(Boom(ErrorKind::WriteZero)))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Boom>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn reader_and_writer_map_errors() {
+        let mut buf = [0; 4];
+        let err = block_on(ERead::read(&mut Reader::new(Failing), &mut buf)).unwrap_err();
+        assert_eq!(EError::kind(&err), EErrorKind::InvalidData);
+
+        let mut w = Writer::new(Failing);
+        assert_eq!(block_on(EWrite::write(&mut w, b"ab")).unwrap(), 2);
+        let err = block_on(EWrite::flush(&mut w)).unwrap_err();
+        assert!(matches!(err, WriteError::Flush(Boom(ErrorKind::WriteZero))));
+        assert_eq!(EError::kind(&err), EErrorKind::WriteZero);
+    }
+}
@@ -0,0 +1,226 @@
+use core::fmt;
+use void::Void;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    UnexpectedEof,
+    WriteZero,
+    WouldBlock,
+    Interrupted,
+    InvalidInput,
+    InvalidData,
+    Other,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorKind::UnexpectedEof => "unexpected end of file",
+            ErrorKind::WriteZero => "write zero",
+            ErrorKind::WouldBlock => "operation would block",
+            ErrorKind::Interrupted => "operation interrupted",
+            ErrorKind::InvalidInput => "invalid input parameter",
+            ErrorKind::InvalidData => "invalid data",
+            ErrorKind::Other => "other error",
+        })
+    }
+}
+
+pub trait Classify {
+    fn kind(&self) -> ErrorKind;
+
+    fn is_eof(&self) -> bool {
+        self.kind() == ErrorKind::UnexpectedEof
+    }
+
+    fn is_would_block(&self) -> bool {
+        self.kind() == ErrorKind::WouldBlock
+    }
+
+    fn is_interrupted(&self) -> bool {
+        self.kind() == ErrorKind::Interrupted
+    }
+}
+
+impl Classify for Void {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+impl<T: Classify + ?Sized> Classify for &T {
+    fn kind(&self) -> ErrorKind {
+        (**self).kind()
+    }
+}
+
+#[cfg(feature = "std")]
+pub type BoxError = std::boxed::Box<dyn std::error::Error + Send + Sync>;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub type BoxError = alloc::boxed::Box<dyn fmt::Debug + Send + Sync>;
+
+/// Conversion into [`BoxError`], used by `box_err`.
+///
+/// Under `std` this covers everything that converts into a boxed `std::error::Error`,
+/// otherwise any `Debug` error.
+#[cfg(feature = "alloc")]
+pub trait IntoBoxError {
+    fn into_box_error(self) -> BoxError;
+}
+
+#[cfg(feature = "std")]
+impl<E: Into<BoxError>> IntoBoxError for E {
+    fn into_box_error(self) -> BoxError {
+        self.into()
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+impl<E: fmt::Debug + Send + Sync + 'static> IntoBoxError for E {
+    fn into_box_error(self) -> BoxError {
+        alloc::boxed::Box::new(self)
+    }
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use super::{Classify, ErrorKind};
+    use std::io;
+
+    impl Classify for io::Error {
+        fn kind(&self) -> ErrorKind {
+            self.kind().into()
+        }
+    }
+
+    impl From<io::ErrorKind> for ErrorKind {
+        fn from(kind: io::ErrorKind) -> Self {
+            match kind {
+                io::ErrorKind::UnexpectedEof => ErrorKind::UnexpectedEof,
+                io::ErrorKind::WriteZero => ErrorKind::WriteZero,
+                io::ErrorKind::WouldBlock => ErrorKind::WouldBlock,
+                io::ErrorKind::Interrupted => ErrorKind::Interrupted,
+                io::ErrorKind::InvalidInput => ErrorKind::InvalidInput,
+                io::ErrorKind::InvalidData => ErrorKind::InvalidData,
+                _ => ErrorKind::Other,
+            }
+        }
+    }
+
+    impl From<ErrorKind> for io::ErrorKind {
+        fn from(kind: ErrorKind) -> Self {
+            match kind {
+                ErrorKind::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+                ErrorKind::WriteZero => io::ErrorKind::WriteZero,
+                ErrorKind::WouldBlock => io::ErrorKind::WouldBlock,
+                ErrorKind::Interrupted => io::ErrorKind::Interrupted,
+                ErrorKind::InvalidInput => io::ErrorKind::InvalidInput,
+                ErrorKind::InvalidData => io::ErrorKind::InvalidData,
+                ErrorKind::Other => io::ErrorKind::Other,
+            }
+        }
+    }
+
+    pub(crate) fn into_io_error<E>(error: E) -> io::Error
+    where
+        E: Classify + Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let kind = error.kind().into();
+        match error.into().downcast::<io::Error>() {
+            Ok(error) => *error,
+            Err(error) => io::Error::new(kind, error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) use io::into_io_error;
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::{error::Error, io};
+
+    const KINDS: [ErrorKind; 7] = [
+        ErrorKind::UnexpectedEof,
+        ErrorKind::WriteZero,
+        ErrorKind::WouldBlock,
+        ErrorKind::Interrupted,
+        ErrorKind::InvalidInput,
+        ErrorKind::InvalidData,
+        ErrorKind::Other,
+    ];
+
+    #[derive(Debug)]
+    struct Boom {
+        kind: ErrorKind,
+        source: Option<io::Error>,
+    }
+
+    impl fmt::Display for Boom {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("boom")
+        }
+    }
+
+    impl Error for Boom {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_ref().map(|e| e as _)
+        }
+    }
+
+    impl Classify for Boom {
+        fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    #[test]
+    fn kinds_round_trip_through_io() {
+        for &kind in &KINDS {
+            let io_kind = io::ErrorKind::from(kind);
+            assert_eq!(ErrorKind::from(io_kind), kind);
+            assert_eq!(io::Error::from(io_kind).kind(), io_kind);
+            assert_eq!(Classify::kind(&io::Error::from(io_kind)), kind);
+        }
+        assert_eq!(ErrorKind::from(io::ErrorKind::NotFound), ErrorKind::Other);
+    }
+
+    #[test]
+    fn into_io_error_uses_the_classified_kind() {
+        for &kind in &KINDS {
+            let error = into_io_error(Boom { kind, source: None });
+            assert_eq!(error.kind(), io::ErrorKind::from(kind));
+            assert_eq!(error.to_string(), "boom");
+        }
+    }
+
+    #[test]
+    fn into_io_error_keeps_the_error_and_its_source() {
+        let error = into_io_error(Boom {
+            kind: ErrorKind::InvalidData,
+            source: Some(io::Error::new(io::ErrorKind::NotFound, "missing")),
+        });
+        assert_eq!(error.source().unwrap().to_string(), "missing");
+        let boom = error.into_inner().unwrap().downcast::<Boom>().unwrap();
+        assert_eq!(boom.kind, ErrorKind::InvalidData);
+        assert_eq!(boom.source.unwrap().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn into_io_error_passes_io_errors_through() {
+        // `NotFound` classifies as `Other`; passing the error through must keep the original.
+        let error = into_io_error(io::Error::new(io::ErrorKind::NotFound, "gone"));
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "gone");
+        assert!(error
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<io::Error>()
+            .is_none());
+
+        let error = into_io_error(io::Error::from_raw_os_error(2));
+        assert_eq!(error.raw_os_error(), Some(2));
+    }
+}
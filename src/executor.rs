@@ -0,0 +1,72 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "std")]
+mod park {
+    use _futures::task::{waker, ArcWake};
+    use core::sync::atomic::{AtomicBool, Ordering};
+    use std::{
+        sync::Arc,
+        task::Waker,
+        thread::{self, Thread},
+    };
+
+    pub(super) struct Parker {
+        woken: AtomicBool,
+        thread: Thread,
+    }
+
+    impl ArcWake for Parker {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.woken.store(true, Ordering::Release);
+            arc_self.thread.unpark();
+        }
+    }
+
+    pub(super) fn parker() -> (Arc<Parker>, Waker) {
+        let parker = Arc::new(Parker {
+            woken: AtomicBool::new(false),
+            thread: thread::current(),
+        });
+        let waker = waker(parker.clone());
+        (parker, waker)
+    }
+
+    impl Parker {
+        pub(super) fn park(&self) {
+            while !self.woken.swap(false, Ordering::Acquire) {
+                thread::park();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn block_on<F: Future>(mut future: F) -> F::Output {
+    let (parker, waker) = park::parker();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        parker.park();
+    }
+}
+
+#[cfg(not(feature = "std"))]
+pub fn block_on<F: Future>(mut future: F) -> F::Output {
+    let mut cx = Context::from_waker(_futures::task::noop_waker_ref());
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,51 @@
+use crate::AsyncBufRead;
+use _futures::ready;
+use bytes::Bytes;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Cancellation safe: if dropped before completing, no data was consumed.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadChunk<'a, R: ?Sized> {
+    reader: Pin<&'a mut R>,
+    max: usize,
+}
+
+impl<R> Future for ReadChunk<'_, R>
+where
+    R: AsyncBufRead + ?Sized,
+{
+    type Output = Result<Bytes, R::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        let chunk = ready!(me.reader.as_mut().poll_read_bytes(cx, me.max))?;
+        Poll::Ready(Ok(chunk))
+    }
+}
+
+pub trait AsyncBufReadExt: AsyncBufRead {
+    fn consume(&mut self, amt: usize)
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).consume(amt)
+    }
+
+    fn read_chunk(&mut self, max: usize) -> ReadChunk<'_, Self>
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).read_chunk_pinned(max)
+    }
+
+    fn read_chunk_pinned(self: Pin<&mut Self>, max: usize) -> ReadChunk<'_, Self> {
+        ReadChunk { reader: self, max }
+    }
+}
+
+impl<R: AsyncBufRead + ?Sized> AsyncBufReadExt for R {}
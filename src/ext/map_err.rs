@@ -0,0 +1,534 @@
+use crate::{AsyncBufRead, AsyncRead, AsyncWrite, BorrowedCursor};
+use bytes::Bytes;
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use pin_project_lite::pin_project;
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct MapErr<R, F> {
+        #[pin]
+        inner: R,
+        f: F,
+    }
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct MapWriteErr<W, F, G, H> {
+        #[pin]
+        inner: W,
+        write: F,
+        flush: G,
+        close: H,
+    }
+}
+
+pub(crate) fn map_err<R, F>(inner: R, f: F) -> MapErr<R, F> {
+    MapErr { inner, f }
+}
+
+pub(crate) fn map_write_err<W, F, G, H>(
+    inner: W,
+    write: F,
+    flush: G,
+    close: H,
+) -> MapWriteErr<W, F, G, H> {
+    MapWriteErr {
+        inner,
+        write,
+        flush,
+        close,
+    }
+}
+
+impl<R, F> MapErr<R, F> {
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<W, F, G, H> MapWriteErr<W, F, G, H> {
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<R, F, E> AsyncRead for MapErr<R, F>
+where
+    R: AsyncRead,
+    F: FnMut(R::Error) -> E,
+{
+    type Error = E;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.project();
+        me.inner.poll_read(cx, buf).map_err(me.f)
+    }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.project();
+        me.inner.poll_read_uninit(cx, buf).map_err(me.f)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        let me = self.project();
+        me.inner.poll_read_ready(cx).map_err(me.f)
+    }
+}
+
+impl<R, F, E> AsyncBufRead for MapErr<R, F>
+where
+    R: AsyncBufRead,
+    F: FnMut(R::Error) -> E,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<&[u8], Self::Error>> {
+        let me = self.project();
+        me.inner.poll_fill_buf(cx).map_err(me.f)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.project().inner.consume(amt)
+    }
+
+    fn poll_read_bytes(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        max: usize,
+    ) -> Poll<Result<Bytes, Self::Error>> {
+        let me = self.project();
+        me.inner.poll_read_bytes(cx, max).map_err(me.f)
+    }
+}
+
+impl<W, F, G, H, WE, FE, CE> AsyncWrite for MapWriteErr<W, F, G, H>
+where
+    W: AsyncWrite,
+    F: FnMut(W::WriteError) -> WE,
+    G: FnMut(W::FlushError) -> FE,
+    H: FnMut(W::CloseError) -> CE,
+{
+    type WriteError = WE;
+    type FlushError = FE;
+    type CloseError = CE;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        let me = self.project();
+        me.inner.poll_write(cx, buf).map_err(me.write)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        let me = self.project();
+        me.inner.poll_flush(cx).map_err(me.flush)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        let me = self.project();
+        me.inner.poll_close(cx).map_err(me.close)
+    }
+
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        let me = self.project();
+        me.inner.poll_write_ready(cx).map_err(me.write)
+    }
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! convert_err {
+    ($name:ident, $error:ty, $convert:path, $($bound:tt)*) => {
+        impl<R> AsyncRead for $name<R>
+        where
+            R: AsyncRead,
+            R::Error: $($bound)*,
+        {
+            type Error = $error;
+
+            fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8])
+                -> Poll<Result<usize, Self::Error>>
+            {
+                self.project().inner.poll_read(cx, buf).map_err($convert)
+            }
+
+            fn poll_read_uninit(self: Pin<&mut Self>, cx: &mut Context, buf: BorrowedCursor<'_>)
+                -> Poll<Result<usize, Self::Error>>
+            {
+                self.project().inner.poll_read_uninit(cx, buf).map_err($convert)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+
+            fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+                self.project().inner.poll_read_ready(cx).map_err($convert)
+            }
+        }
+
+        impl<R> AsyncBufRead for $name<R>
+        where
+            R: AsyncBufRead,
+            R::Error: $($bound)*,
+        {
+            fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context)
+                -> Poll<Result<&[u8], Self::Error>>
+            {
+                self.project().inner.poll_fill_buf(cx).map_err($convert)
+            }
+
+            fn consume(self: Pin<&mut Self>, amt: usize) {
+                self.project().inner.consume(amt)
+            }
+
+            fn poll_read_bytes(self: Pin<&mut Self>, cx: &mut Context, max: usize)
+                -> Poll<Result<Bytes, Self::Error>>
+            {
+                self.project().inner.poll_read_bytes(cx, max).map_err($convert)
+            }
+        }
+
+        impl<W> AsyncWrite for $name<W>
+        where
+            W: AsyncWrite,
+            W::WriteError: $($bound)*,
+            W::FlushError: $($bound)*,
+            W::CloseError: $($bound)*,
+        {
+            type WriteError = $error;
+            type FlushError = $error;
+            type CloseError = $error;
+
+            fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8])
+                -> Poll<Result<usize, Self::WriteError>>
+            {
+                self.project().inner.poll_write(cx, buf).map_err($convert)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+                self.project().inner.poll_flush(cx).map_err($convert)
+            }
+
+            fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+                self.project().inner.poll_close(cx).map_err($convert)
+            }
+
+            fn poll_write_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::WriteError>> {
+                self.project().inner.poll_write_ready(cx).map_err($convert)
+            }
+        }
+
+        impl<T> $name<T> {
+            pub fn get_ref(&self) -> &T {
+                &self.inner
+            }
+
+            pub fn get_mut(&mut self) -> &mut T {
+                &mut self.inner
+            }
+
+            pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+                self.project().inner
+            }
+
+            pub fn into_inner(self) -> T {
+                self.inner
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod boxed {
+    use super::*;
+    use crate::{BoxError, IntoBoxError};
+
+    pin_project! {
+        /// Converts every error of the inner reader or writer into a [`BoxError`].
+        #[derive(Debug)]
+        pub struct BoxErr<T> {
+            #[pin]
+            inner: T,
+        }
+    }
+
+    pub(crate) fn box_err<T>(inner: T) -> BoxErr<T> {
+        BoxErr { inner }
+    }
+
+    convert_err!(BoxErr, BoxError, IntoBoxError::into_box_error, IntoBoxError);
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) use boxed::box_err;
+#[cfg(feature = "alloc")]
+pub use boxed::BoxErr;
+
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use crate::{error::into_io_error, Classify};
+    use std::{boxed::Box, error::Error, io};
+
+    pin_project! {
+        /// Converts every error of the inner reader or writer into an [`io::Error`],
+        /// keeping its [`Classify`] kind.
+        #[derive(Debug)]
+        pub struct IntoIo<T> {
+            #[pin]
+            inner: T,
+        }
+    }
+
+    pub(crate) fn into_io<T>(inner: T) -> IntoIo<T> {
+        IntoIo { inner }
+    }
+
+    convert_err!(
+        IntoIo,
+        io::Error,
+        into_io_error,
+        Classify + Into<Box<dyn Error + Send + Sync>>
+    );
+}
+
+#[cfg(feature = "std")]
+pub(crate) use io::into_io;
+#[cfg(feature = "std")]
+pub use io::IntoIo;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt, AsyncWriteExt, Classify, ErrorKind};
+    use core::fmt;
+
+    #[derive(Debug, PartialEq)]
+    struct Boom(&'static str, ErrorKind);
+
+    impl fmt::Display for Boom {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} failed", self.0)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for Boom {}
+
+    impl Classify for Boom {
+        fn kind(&self) -> ErrorKind {
+            self.1
+        }
+    }
+
+    /// Fails every operation with an error naming it.
+    struct Failing;
+
+    impl AsyncRead for Failing {
+        type Error = Boom;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            Poll::Ready(Err(Boom("read", ErrorKind::UnexpectedEof)))
+        }
+    }
+
+    impl AsyncWrite for Failing {
+        type WriteError = Boom;
+        type FlushError = Boom;
+        type CloseError = Boom;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            Poll::Ready(Err(Boom("write", ErrorKind::WriteZero)))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Err(Boom("flush", ErrorKind::Interrupted)))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Err(Boom("close", ErrorKind::WouldBlock)))
+        }
+    }
+
+    #[test]
+    fn map_err_maps_read_errors() {
+        let mut reader = Failing.map_err(|e| (1, e));
+        let err = block_on(reader.read(&mut [0; 4])).unwrap_err();
+        assert_eq!(err, (1, Boom("read", ErrorKind::UnexpectedEof)));
+    }
+
+    #[test]
+    fn map_write_err_uses_the_matching_closure() {
+        let mut writer = Failing.map_write_err(|e| ("w", e.0), |e| ("f", e.0), |e| ("c", e.0));
+        assert_eq!(block_on(writer.write(b"x")).unwrap_err(), ("w", "write"));
+        assert_eq!(block_on(writer.flush()).unwrap_err(), ("f", "flush"));
+        assert_eq!(block_on(writer.close()).unwrap_err(), ("c", "close"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn box_err_keeps_the_error() {
+        let mut reader = Failing.box_err();
+        let err = block_on(reader.read(&mut [0; 4])).unwrap_err();
+        assert_eq!(alloc::format!("{:?}", err), "Boom(\"read\", UnexpectedEof)");
+
+        let mut writer = Failing.box_write_err();
+        let err = block_on(writer.close()).unwrap_err();
+        assert_eq!(alloc::format!("{:?}", err), "Boom(\"close\", WouldBlock)");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn into_io_keeps_the_classified_kind() {
+        use std::io;
+
+        let mut reader = Failing.into_io();
+        let err = block_on(reader.read(&mut [0; 4])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(err.to_string(), "read failed");
+
+        let mut writer = Failing.into_io_write();
+        let write = block_on(writer.write(b"x")).unwrap_err();
+        assert_eq!(write.kind(), io::ErrorKind::WriteZero);
+        let flush = block_on(writer.flush()).unwrap_err();
+        assert_eq!(flush.kind(), io::ErrorKind::Interrupted);
+        let close = block_on(writer.close()).unwrap_err();
+        assert_eq!(close.kind(), io::ErrorKind::WouldBlock);
+        let inner = close.into_inner().unwrap();
+        assert_eq!(
+            *inner.downcast::<Boom>().unwrap(),
+            Boom("close", ErrorKind::WouldBlock)
+        );
+    }
+
+    /// Reports a fixed readiness for reads and writes.
+    struct Gate(Poll<Result<(), &'static str>>);
+
+    impl AsyncRead for Gate {
+        type Error = &'static str;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            Poll::Ready(Ok(0))
+        }
+
+        fn poll_read_ready(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::Error>> {
+            self.0
+        }
+    }
+
+    impl AsyncWrite for Gate {
+        type WriteError = &'static str;
+        type FlushError = &'static str;
+        type CloseError = &'static str;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_write_ready(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+        ) -> Poll<Result<(), Self::WriteError>> {
+            self.0
+        }
+    }
+
+    fn noop_cx() -> Context<'static> {
+        Context::from_waker(_futures::task::noop_waker_ref())
+    }
+
+    #[test]
+    fn readiness_is_forwarded_and_mapped() {
+        let mut cx = noop_cx();
+        let mut pending = Gate(Poll::Pending).map_err(|e| (1, e));
+        assert!(Pin::new(&mut pending).poll_read_ready(&mut cx).is_pending());
+        let mut ready = Gate(Poll::Ready(Ok(()))).map_err(|e| (1, e));
+        assert!(matches!(
+            Pin::new(&mut ready).poll_read_ready(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+        let mut failed = Gate(Poll::Ready(Err("read"))).map_err(|e| (1, e));
+        assert!(matches!(
+            Pin::new(&mut failed).poll_read_ready(&mut cx),
+            Poll::Ready(Err((1, "read")))
+        ));
+
+        let writer = |ready| Gate(ready).map_write_err(|e| ("w", e), |e| ("f", e), |e| ("c", e));
+        let mut pending = writer(Poll::Pending);
+        assert!(Pin::new(&mut pending)
+            .poll_write_ready(&mut cx)
+            .is_pending());
+        let mut failed = writer(Poll::Ready(Err("write")));
+        assert!(matches!(
+            Pin::new(&mut failed).poll_write_ready(&mut cx),
+            Poll::Ready(Err(("w", "write")))
+        ));
+    }
+}
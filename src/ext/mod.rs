@@ -1,3 +1,11 @@
+mod buf_read;
+pub use buf_read::{AsyncBufReadExt, ReadChunk};
+mod map_err;
+#[cfg(feature = "alloc")]
+pub use map_err::BoxErr;
+#[cfg(feature = "std")]
+pub use map_err::IntoIo;
+pub use map_err::{MapErr, MapWriteErr};
 mod read;
 pub use read::AsyncReadExt;
 mod write;
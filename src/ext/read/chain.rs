@@ -1,4 +1,4 @@
-use crate::AsyncRead;
+use crate::{AsyncRead, BorrowedCursor, Classify, ErrorKind};
 
 use _futures::ready;
 use core::{
@@ -74,6 +74,34 @@ pub enum ChainError<T, U> {
     Right(U),
 }
 
+impl<T: fmt::Display, U: fmt::Display> fmt::Display for ChainError<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Left(e) => e.fmt(f),
+            ChainError::Right(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::error::Error, U: std::error::Error> std::error::Error for ChainError<T, U> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChainError::Left(e) => e.source(),
+            ChainError::Right(e) => e.source(),
+        }
+    }
+}
+
+impl<T: Classify, U: Classify> Classify for ChainError<T, U> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ChainError::Left(e) => e.kind(),
+            ChainError::Right(e) => e.kind(),
+        }
+    }
+}
+
 impl<T, U> AsyncRead for Chain<T, U>
 where
     T: AsyncRead,
@@ -96,4 +124,110 @@ where
         }
         me.second.poll_read(cx, buf).map_err(ChainError::Right)
     }
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, ChainError<T::Error, U::Error>>> {
+        let me = self.project();
+
+        if !*me.done_first {
+            let start = buf.written();
+            ready!(me
+                .first
+                .poll_read_uninit(cx, buf.reborrow())
+                .map_err(ChainError::Left))?;
+            match buf.written() - start {
+                0 if buf.remaining() != 0 => *me.done_first = true,
+                n => return Poll::Ready(Ok(n)),
+            }
+        }
+        me.second
+            .poll_read_uninit(cx, buf)
+            .map_err(ChainError::Right)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.second.size_hint();
+        if self.done_first {
+            return (lower, upper);
+        }
+        let (first_lower, first_upper) = self.first.size_hint();
+        let upper = match (first_upper, upper) {
+            (Some(first), Some(second)) => first.checked_add(second),
+            _ => None,
+        };
+        (first_lower.saturating_add(lower), upper)
+    }
+
+    fn poll_read_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), ChainError<T::Error, U::Error>>> {
+        let me = self.project();
+
+        if !*me.done_first {
+            return me.first.poll_read_ready(cx).map_err(ChainError::Left);
+        }
+        me.second.poll_read_ready(cx).map_err(ChainError::Right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt};
+
+    /// Reports a fixed readiness and reads `data` regardless of it.
+    struct Gate {
+        data: &'static [u8],
+        ready: Poll<Result<(), &'static str>>,
+    }
+
+    impl AsyncRead for Gate {
+        type Error = &'static str;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = buf.len().min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_read_ready(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::Error>> {
+            self.ready
+        }
+    }
+
+    fn read_ready<R: AsyncRead + Unpin>(reader: &mut R) -> Poll<Result<(), R::Error>> {
+        let mut cx = Context::from_waker(_futures::task::noop_waker_ref());
+        Pin::new(reader).poll_read_ready(&mut cx)
+    }
+
+    #[test]
+    fn readiness_follows_the_active_reader() {
+        let mut reader = Gate {
+            data: b"a",
+            ready: Poll::Pending,
+        }
+        .chain(Gate {
+            data: b"b",
+            ready: Poll::Ready(Err("second")),
+        });
+        assert!(read_ready(&mut reader).is_pending());
+
+        let mut buf = [0; 2];
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 1);
+        assert!(read_ready(&mut reader).is_pending());
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 1);
+        assert_eq!(&buf[..1], b"b");
+        assert!(matches!(
+            read_ready(&mut reader),
+            Poll::Ready(Err(ChainError::Right("second")))
+        ));
+    }
 }
@@ -1,35 +1,62 @@
-use crate::AsyncRead;
-use bytes::BufMut;
+#[cfg(feature = "alloc")]
+use super::map_err::{box_err, BoxErr};
+#[cfg(feature = "std")]
+use super::map_err::{into_io, IntoIo};
+use super::map_err::{map_err, MapErr};
+#[cfg(feature = "std")]
+use crate::Classify;
+#[cfg(feature = "alloc")]
+use crate::IntoBoxError;
+use crate::{poll::try_once, AsyncRead};
+#[cfg(feature = "alloc")]
+use crate::{AsyncWrite, BoxedDuplex, BoxedReader, LocalBoxedDuplex, LocalBoxedReader};
+use bytes::{Buf, BufMut};
+use core::{future::Future, pin::Pin};
 mod chain;
+#[allow(clippy::module_inception)]
 mod read;
 mod read_buf;
+mod read_bytes;
 mod read_exact;
 mod read_int;
 #[cfg(feature = "alloc")]
 mod read_to_end;
 #[cfg(feature = "alloc")]
 mod read_to_string;
+#[cfg(feature = "alloc")]
+mod read_to_string_lossy;
+mod readable;
 mod take;
 #[cfg(feature = "alloc")]
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, vec::Vec};
 use chain::chain;
-pub use chain::Chain;
+pub use chain::{Chain, ChainError};
 use read::read;
 pub use read::Read;
 use read_buf::read_buf;
 pub use read_buf::ReadBuf;
-use read_exact::read_exact;
-pub use read_exact::ReadExact;
+use read_bytes::{read_bytes, read_exact_bytes};
+pub use read_bytes::{ReadBytes, ReadExactBytes};
+use read_exact::{read_exact, read_exact_resumable};
+pub use read_exact::{ReadExact, ReadExactError, ReadExactResumable, ReadExactState};
+use read_int::read_int_resumable;
+pub use read_int::{PartialInt, ReadIntError, ReadIntResumable, ReadIntState};
 pub use read_int::{ReadI128, ReadI16, ReadI32, ReadI64, ReadI8};
 pub use read_int::{ReadU128, ReadU16, ReadU32, ReadU64, ReadU8};
 #[cfg(feature = "alloc")]
-use read_to_end::read_to_end;
+use read_to_end::{read_to_end, read_to_end_limited};
+#[cfg(feature = "alloc")]
+pub use read_to_end::{ReadToEnd, ReadToEndError, ReadToEndLimited};
+#[cfg(feature = "alloc")]
+use read_to_string::{read_to_string, read_to_string_limited};
 #[cfg(feature = "alloc")]
-pub use read_to_end::ReadToEnd;
+pub use read_to_string::{InvalidUtf8, ReadToString, ReadToStringError};
 #[cfg(feature = "alloc")]
-use read_to_string::read_to_string;
+use read_to_string_lossy::read_to_string_lossy;
 #[cfg(feature = "alloc")]
-pub use read_to_string::ReadToString;
+pub use read_to_string_lossy::ReadToStringLossy;
+use readable::readable;
+pub use readable::Readable;
 use take::take;
 pub use take::Take;
 
@@ -38,13 +65,39 @@ macro_rules! read_impl {
         $(
             $(#[$outer:meta])*
             fn $name:ident(&mut self) -> $($fut:ident)*;
+            fn $pinned:ident(self: Pin<&mut Self>);
         )*
     ) => {
         $(
             $(#[$outer])*
-            fn $name<'a>(&'a mut self) -> $($fut)*<&'a mut Self> where Self: Unpin {
+            fn $name(&mut self) -> $($fut)*<&mut Self> where Self: Unpin {
                 $($fut)*::new(self)
             }
+
+            $(#[$outer])*
+            fn $pinned(self: Pin<&mut Self>) -> $($fut)*<Pin<&mut Self>> {
+                $($fut)*::new(self)
+            }
+        )*
+    }
+}
+
+macro_rules! read_resumable_impl {
+    ($($name:ident, $pinned:ident, $ty:ty, $get:ident;)*) => {
+        $(
+            fn $name<'a>(&'a mut self, state: &'a mut ReadIntState<$ty>) -> ReadIntResumable<'a, Self, $ty>
+            where
+                Self: Unpin,
+            {
+                read_int_resumable(Pin::new(self), state, |b: &mut &[u8]| b.$get())
+            }
+
+            fn $pinned<'a>(
+                self: Pin<&'a mut Self>,
+                state: &'a mut ReadIntState<$ty>,
+            ) -> ReadIntResumable<'a, Self, $ty> {
+                read_int_resumable(self, state, |b: &mut &[u8]| b.$get())
+            }
         )*
     }
 }
@@ -62,12 +115,23 @@ pub trait AsyncReadExt: AsyncRead {
     where
         Self: Unpin,
     {
+        read(Pin::new(self), buf)
+    }
+
+    fn read_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> Read<'a, Self> {
         read(self, buf)
     }
 
     fn read_buf<'a, B>(&'a mut self, buf: &'a mut B) -> ReadBuf<'a, Self, B>
     where
-        Self: Sized,
+        Self: Unpin,
+        B: BufMut,
+    {
+        read_buf(Pin::new(self), buf)
+    }
+
+    fn read_buf_pinned<'a, B>(self: Pin<&'a mut Self>, buf: &'a mut B) -> ReadBuf<'a, Self, B>
+    where
         B: BufMut,
     {
         read_buf(self, buf)
@@ -77,20 +141,134 @@ pub trait AsyncReadExt: AsyncRead {
     where
         Self: Unpin,
     {
+        read_exact(Pin::new(self), buf)
+    }
+
+    fn read_exact_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> ReadExact<'a, Self> {
         read_exact(self, buf)
     }
 
+    fn read_exact_resumable<'a>(
+        &'a mut self,
+        buf: &'a mut [u8],
+        state: &'a mut ReadExactState,
+    ) -> ReadExactResumable<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_exact_resumable(Pin::new(self), buf, state)
+    }
+
+    fn read_exact_resumable_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        buf: &'a mut [u8],
+        state: &'a mut ReadExactState,
+    ) -> ReadExactResumable<'a, Self> {
+        read_exact_resumable(self, buf, state)
+    }
+
+    fn readable(&mut self) -> Readable<'_, Self>
+    where
+        Self: Unpin,
+    {
+        readable(Pin::new(self))
+    }
+
+    fn readable_pinned(self: Pin<&mut Self>) -> Readable<'_, Self> {
+        readable(self)
+    }
+
+    /// Polls a single read with a no-op waker, returning `Ok(None)` if the reader is not ready.
+    fn try_read(&mut self, buf: &mut [u8]) -> Result<Option<usize>, Self::Error>
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).try_read_pinned(buf)
+    }
+
+    fn try_read_pinned(self: Pin<&mut Self>, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+        try_once(|cx| self.poll_read(cx, buf))
+    }
+
+    /// Reads as much of `buf` as is available without waiting, returning `Ok(None)` if it is not
+    /// yet full. Progress is kept in `state`, so the call can be repeated with the same buffer
+    /// until it returns `Ok(Some(_))`.
+    fn try_read_exact(
+        &mut self,
+        buf: &mut [u8],
+        state: &mut ReadExactState,
+    ) -> Result<Option<usize>, ReadExactError<Self::Error>>
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).try_read_exact_pinned(buf, state)
+    }
+
+    fn try_read_exact_pinned(
+        self: Pin<&mut Self>,
+        buf: &mut [u8],
+        state: &mut ReadExactState,
+    ) -> Result<Option<usize>, ReadExactError<Self::Error>> {
+        let mut fut = read_exact_resumable(self, buf, state);
+        try_once(|cx| Pin::new(&mut fut).poll(cx))
+    }
+
+    fn read_bytes(&mut self, len: usize) -> ReadBytes<'_, Self>
+    where
+        Self: Unpin,
+    {
+        read_bytes(Pin::new(self), len)
+    }
+
+    fn read_bytes_pinned(self: Pin<&mut Self>, len: usize) -> ReadBytes<'_, Self> {
+        read_bytes(self, len)
+    }
+
+    fn read_exact_bytes(&mut self, len: usize) -> ReadExactBytes<'_, Self>
+    where
+        Self: Unpin,
+    {
+        read_exact_bytes(Pin::new(self), len)
+    }
+
+    fn read_exact_bytes_pinned(self: Pin<&mut Self>, len: usize) -> ReadExactBytes<'_, Self> {
+        read_exact_bytes(self, len)
+    }
+
     read_impl! {
         fn read_u8(&mut self) -> ReadU8;
+        fn read_u8_pinned(self: Pin<&mut Self>);
         fn read_i8(&mut self) -> ReadI8;
+        fn read_i8_pinned(self: Pin<&mut Self>);
         fn read_u16(&mut self) -> ReadU16;
+        fn read_u16_pinned(self: Pin<&mut Self>);
         fn read_i16(&mut self) -> ReadI16;
+        fn read_i16_pinned(self: Pin<&mut Self>);
         fn read_u32(&mut self) -> ReadU32;
+        fn read_u32_pinned(self: Pin<&mut Self>);
         fn read_i32(&mut self) -> ReadI32;
+        fn read_i32_pinned(self: Pin<&mut Self>);
         fn read_u64(&mut self) -> ReadU64;
+        fn read_u64_pinned(self: Pin<&mut Self>);
         fn read_i64(&mut self) -> ReadI64;
+        fn read_i64_pinned(self: Pin<&mut Self>);
         fn read_u128(&mut self) -> ReadU128;
+        fn read_u128_pinned(self: Pin<&mut Self>);
         fn read_i128(&mut self) -> ReadI128;
+        fn read_i128_pinned(self: Pin<&mut Self>);
+    }
+
+    read_resumable_impl! {
+        read_u8_resumable, read_u8_resumable_pinned, u8, get_u8;
+        read_i8_resumable, read_i8_resumable_pinned, i8, get_i8;
+        read_u16_resumable, read_u16_resumable_pinned, u16, get_u16;
+        read_i16_resumable, read_i16_resumable_pinned, i16, get_i16;
+        read_u32_resumable, read_u32_resumable_pinned, u32, get_u32;
+        read_i32_resumable, read_i32_resumable_pinned, i32, get_i32;
+        read_u64_resumable, read_u64_resumable_pinned, u64, get_u64;
+        read_i64_resumable, read_i64_resumable_pinned, i64, get_i64;
+        read_u128_resumable, read_u128_resumable_pinned, u128, get_u128;
+        read_i128_resumable, read_i128_resumable_pinned, i128, get_i128;
     }
 
     #[cfg(feature = "alloc")]
@@ -98,23 +276,337 @@ pub trait AsyncReadExt: AsyncRead {
     where
         Self: Unpin,
     {
+        read_to_end(Pin::new(self), buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_to_end_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        buf: &'a mut Vec<u8>,
+    ) -> ReadToEnd<'a, Self> {
         read_to_end(self, buf)
     }
 
+    #[cfg(feature = "alloc")]
+    fn read_to_end_limited<'a>(
+        &'a mut self,
+        buf: &'a mut Vec<u8>,
+        limit: usize,
+    ) -> ReadToEndLimited<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_to_end_limited(Pin::new(self), buf, limit)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_to_end_limited_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        buf: &'a mut Vec<u8>,
+        limit: usize,
+    ) -> ReadToEndLimited<'a, Self> {
+        read_to_end_limited(self, buf, limit)
+    }
+
     #[cfg(feature = "alloc")]
     fn read_to_string<'a>(&'a mut self, dst: &'a mut String) -> ReadToString<'a, Self>
     where
         Self: Unpin,
     {
+        read_to_string(Pin::new(self), dst)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_to_string_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        dst: &'a mut String,
+    ) -> ReadToString<'a, Self> {
         read_to_string(self, dst)
     }
 
+    #[cfg(feature = "alloc")]
+    fn read_to_string_limited<'a>(
+        &'a mut self,
+        dst: &'a mut String,
+        limit: usize,
+    ) -> ReadToString<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_to_string_limited(Pin::new(self), dst, limit)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_to_string_limited_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        dst: &'a mut String,
+        limit: usize,
+    ) -> ReadToString<'a, Self> {
+        read_to_string_limited(self, dst, limit)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_to_string_lossy<'a>(&'a mut self, dst: &'a mut String) -> ReadToStringLossy<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_to_string_lossy(Pin::new(self), dst)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_to_string_lossy_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        dst: &'a mut String,
+    ) -> ReadToStringLossy<'a, Self> {
+        read_to_string_lossy(self, dst)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed_reader<'a>(self) -> BoxedReader<'a, Self::Error>
+    where
+        Self: Sized + Send + 'a,
+    {
+        Box::pin(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed_local_reader<'a>(self) -> LocalBoxedReader<'a, Self::Error>
+    where
+        Self: Sized + 'a,
+    {
+        Box::pin(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed_duplex<'a>(self) -> BoxedDuplex<'a, Self::Error>
+    where
+        Self: AsyncWrite<WriteError = <Self as AsyncRead>::Error>
+            + AsyncWrite<FlushError = <Self as AsyncRead>::Error>
+            + AsyncWrite<CloseError = <Self as AsyncRead>::Error>
+            + Sized
+            + Send
+            + 'a,
+    {
+        Box::pin(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed_local_duplex<'a>(self) -> LocalBoxedDuplex<'a, Self::Error>
+    where
+        Self: AsyncWrite<WriteError = <Self as AsyncRead>::Error>
+            + AsyncWrite<FlushError = <Self as AsyncRead>::Error>
+            + AsyncWrite<CloseError = <Self as AsyncRead>::Error>
+            + Sized
+            + 'a,
+    {
+        Box::pin(self)
+    }
+
     fn take(self, limit: u64) -> Take<Self>
     where
         Self: Sized,
     {
         take(self, limit)
     }
+
+    fn map_err<F, E>(self, f: F) -> MapErr<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Error) -> E,
+    {
+        map_err(self, f)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn box_err(self) -> BoxErr<Self>
+    where
+        Self: Sized,
+        Self::Error: IntoBoxError,
+    {
+        box_err(self)
+    }
+
+    #[cfg(feature = "std")]
+    fn into_io(self) -> IntoIo<Self>
+    where
+        Self: Sized,
+        Self::Error: Classify + Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        into_io(self)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn into_tokio(self) -> crate::TokioCompat<Self>
+    where
+        Self: Sized,
+    {
+        crate::TokioCompat::new(self)
+    }
+
+    #[cfg(feature = "tokio1")]
+    fn into_tokio1(self) -> crate::Tokio1Compat<Self>
+    where
+        Self: Sized,
+    {
+        crate::Tokio1Compat::new(self)
+    }
+
+    #[cfg(feature = "futures")]
+    fn into_futures(self) -> crate::FuturesCompat<Self>
+    where
+        Self: Sized,
+    {
+        crate::FuturesCompat::new(self)
+    }
 }
 
 impl<R: AsyncRead + ?Sized> AsyncReadExt for R {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_on;
+    use core::{
+        marker::PhantomPinned,
+        pin::pin,
+        task::{Context, Poll},
+    };
+    use pin_project_lite::pin_project;
+    use void::Void;
+
+    pin_project! {
+        /// A `!Unpin` reader, so it can only be used pinned.
+        struct Pinned {
+            data: &'static [u8],
+            #[pin]
+            _pin: PhantomPinned,
+        }
+    }
+
+    fn pinned(data: &'static [u8]) -> Pinned {
+        Pinned {
+            data,
+            _pin: PhantomPinned,
+        }
+    }
+
+    impl AsyncRead for Pinned {
+        type Error = Void;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let data = self.project().data;
+            let n = buf.len().min(data.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            *data = &data[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[test]
+    fn pinned_futures_over_a_trait_object() {
+        let mut reader: Pin<&mut dyn AsyncRead<Error = Void>> =
+            pin!(pinned(b"\x01\x02hello, world"));
+
+        assert_eq!(block_on(reader.as_mut().read_u16_pinned()).unwrap(), 0x0102);
+        let mut buf = [0; 5];
+        block_on(reader.as_mut().read_exact_pinned(&mut buf)).unwrap();
+        assert_eq!(&buf, b"hello");
+        assert_eq!(
+            block_on(reader.as_mut().read_pinned(&mut buf[..2])).unwrap(),
+            2
+        );
+        assert_eq!(&buf[..2], b", ");
+        assert_eq!(
+            block_on(reader.as_mut().read_bytes_pinned(3)).unwrap(),
+            "wor"
+        );
+        assert_eq!(reader.as_mut().try_read_pinned(&mut buf).unwrap(), Some(2));
+        assert_eq!(&buf[..2], b"ld");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn pinned_collecting_futures_over_a_trait_object() {
+        let mut reader: Pin<&mut dyn AsyncRead<Error = Void>> = pin!(pinned(b"abc"));
+        let mut buf = Vec::new();
+        block_on(reader.as_mut().read_to_end_pinned(&mut buf)).unwrap();
+        assert_eq!(buf, b"abc");
+
+        let mut reader: Pin<&mut dyn AsyncRead<Error = Void>> = pin!(pinned(b"def"));
+        let mut buf = String::new();
+        block_on(reader.as_mut().read_to_string_pinned(&mut buf)).unwrap();
+        assert_eq!(buf, "def");
+    }
+
+    /// Replays a script of chunks, where `None` is a single `Pending`.
+    struct Script(&'static [Option<&'static [u8]>]);
+
+    impl AsyncRead for Script {
+        type Error = Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            match self.0.split_first() {
+                None => Poll::Ready(Ok(0)),
+                Some((None, rest)) => {
+                    self.0 = rest;
+                    Poll::Pending
+                }
+                Some((Some(chunk), rest)) => {
+                    let n = buf.len().min(chunk.len());
+                    buf[..n].copy_from_slice(&chunk[..n]);
+                    self.0 = rest;
+                    Poll::Ready(Ok(n))
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn try_read_is_none_while_pending() {
+        let mut reader = Script(&[None, Some(b"ab")]);
+        let mut buf = [0; 4];
+        assert_eq!(reader.try_read(&mut buf).unwrap(), None);
+        assert_eq!(reader.try_read(&mut buf).unwrap(), Some(2));
+        assert_eq!(&buf[..2], b"ab");
+        assert_eq!(reader.try_read(&mut buf).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn try_read_exact_keeps_partial_progress() {
+        let mut reader = Script(&[Some(b"ab"), None, Some(b"c"), None, Some(b"de")]);
+        let mut state = ReadExactState::new();
+        let mut buf = [0; 5];
+
+        assert_eq!(reader.try_read_exact(&mut buf, &mut state).unwrap(), None);
+        assert_eq!(state.position(), 2);
+        assert_eq!(reader.try_read_exact(&mut buf, &mut state).unwrap(), None);
+        assert_eq!(state.position(), 3);
+        assert_eq!(
+            reader.try_read_exact(&mut buf, &mut state).unwrap(),
+            Some(5)
+        );
+        assert_eq!(&buf, b"abcde");
+    }
+
+    #[test]
+    fn try_read_exact_reports_eof_after_partial_progress() {
+        let mut reader = Script(&[Some(b"ab"), None]);
+        let mut state = ReadExactState::new();
+        let mut buf = [0; 4];
+
+        assert_eq!(reader.try_read_exact(&mut buf, &mut state).unwrap(), None);
+        match reader.try_read_exact(&mut buf, &mut state) {
+            Err(ReadExactError::Eof(2)) => {}
+            other => panic!("expected Eof(2), got {:?}", other),
+        }
+        assert_eq!(&buf[..2], b"ab");
+    }
+}
@@ -1,33 +1,33 @@
 use crate::AsyncRead;
 use core::{
     future::Future,
-    marker::Unpin,
     pin::Pin,
     task::{Context, Poll},
 };
 
-pub(crate) fn read<'a, R>(reader: &'a mut R, buf: &'a mut [u8]) -> Read<'a, R>
+pub(crate) fn read<'a, R>(reader: Pin<&'a mut R>, buf: &'a mut [u8]) -> Read<'a, R>
 where
-    R: AsyncRead + Unpin + ?Sized,
+    R: AsyncRead + ?Sized,
 {
     Read { reader, buf }
 }
 
+/// Cancellation safe: if dropped before completing, no data was read.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Read<'a, R: ?Sized> {
-    reader: &'a mut R,
+    reader: Pin<&'a mut R>,
     buf: &'a mut [u8],
 }
 
 impl<R> Future for Read<'_, R>
 where
-    R: AsyncRead + Unpin + ?Sized,
+    R: AsyncRead + ?Sized,
 {
     type Output = Result<usize, R::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<usize, R::Error>> {
         let me = &mut *self;
-        Pin::new(&mut *me.reader).poll_read(cx, me.buf)
+        me.reader.as_mut().poll_read(cx, me.buf)
     }
 }
@@ -6,32 +6,31 @@ use core::{
     task::{Context, Poll},
 };
 
-pub(crate) fn read_buf<'a, R, B>(reader: &'a mut R, buf: &'a mut B) -> ReadBuf<'a, R, B>
+pub(crate) fn read_buf<'a, R, B>(reader: Pin<&'a mut R>, buf: &'a mut B) -> ReadBuf<'a, R, B>
 where
-    R: AsyncRead,
+    R: AsyncRead + ?Sized,
     B: BufMut,
 {
     ReadBuf { reader, buf }
 }
 
+/// Cancellation safe: if dropped before completing, no data was read.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
-pub struct ReadBuf<'a, R, B> {
-    reader: &'a mut R,
+pub struct ReadBuf<'a, R: ?Sized, B> {
+    reader: Pin<&'a mut R>,
     buf: &'a mut B,
 }
 
 impl<R, B> Future for ReadBuf<'_, R, B>
 where
-    R: AsyncRead,
+    R: AsyncRead + ?Sized,
     B: BufMut,
 {
     type Output = Result<usize, R::Error>;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<usize, R::Error>> {
-        unsafe {
-            let me = self.get_unchecked_mut();
-            Pin::new_unchecked(&mut *me.reader).poll_read_buf(cx, &mut me.buf)
-        }
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<usize, R::Error>> {
+        let me = &mut *self;
+        Pin::new(&mut me.reader).poll_read_buf(cx, me.buf)
     }
 }
@@ -0,0 +1,159 @@
+use super::read_exact::ReadExactError;
+use crate::{AsyncRead, BorrowedBuf};
+
+use _futures::ready;
+use bytes::{BufMut, BytesMut};
+use core::{
+    cmp,
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Smallest allocation made when the buffer runs out of room. The buffer grows as data
+/// arrives rather than reserving `len` up front, so an untrusted length can't force a
+/// large allocation by itself.
+const MIN_GROWTH: usize = 8 * 1024;
+
+pub(crate) fn read_bytes<R>(reader: Pin<&mut R>, len: usize) -> ReadBytes<'_, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    ReadBytes {
+        reader,
+        buf: BytesMut::new(),
+        len,
+    }
+}
+
+pub(crate) fn read_exact_bytes<R>(reader: Pin<&mut R>, len: usize) -> ReadExactBytes<'_, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    ReadExactBytes {
+        reader,
+        buf: BytesMut::new(),
+        len,
+    }
+}
+
+/// Cancellation safe: if dropped before completing, no data was read.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadBytes<'a, R: ?Sized> {
+    reader: Pin<&'a mut R>,
+    buf: BytesMut,
+    len: usize,
+}
+
+/// Not cancellation safe: if dropped before completing, the bytes already read are lost.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadExactBytes<'a, R: ?Sized> {
+    reader: Pin<&'a mut R>,
+    buf: BytesMut,
+    len: usize,
+}
+
+fn poll_read_into<R: AsyncRead + ?Sized>(
+    reader: Pin<&mut R>,
+    cx: &mut Context,
+    buf: &mut BytesMut,
+    len: usize,
+) -> Poll<Result<usize, R::Error>> {
+    let want = len - buf.len();
+    if buf.len() == buf.capacity() {
+        buf.reserve(cmp::min(want, cmp::max(buf.capacity(), MIN_GROWTH)));
+    }
+    let n = {
+        let spare = buf.bytes_mut();
+        let max = cmp::min(spare.len(), want);
+        let mut b = BorrowedBuf::uninit(&mut spare[..max]);
+        ready!(reader.poll_read_uninit(cx, b.unfilled()))?;
+        b.filled().len()
+    };
+
+    unsafe {
+        buf.advance_mut(n);
+    }
+    Poll::Ready(Ok(n))
+}
+
+impl<R> Future for ReadBytes<'_, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    type Output = Result<BytesMut, R::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        if me.len > 0 {
+            ready!(poll_read_into(me.reader.as_mut(), cx, &mut me.buf, me.len))?;
+        }
+        Poll::Ready(Ok(mem::take(&mut me.buf)))
+    }
+}
+
+impl<R> Future for ReadExactBytes<'_, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    type Output = Result<BytesMut, ReadExactError<R::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        while me.buf.len() < me.len {
+            let n = ready!(poll_read_into(me.reader.as_mut(), cx, &mut me.buf, me.len))?;
+            if n == 0 {
+                return Poll::Ready(Err(ReadExactError::Eof(me.buf.len())));
+            }
+        }
+        Poll::Ready(Ok(mem::take(&mut me.buf)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt};
+
+    /// Reads at most 1000 bytes at a time.
+    struct Dribble<'a>(&'a [u8]);
+
+    impl AsyncRead for Dribble<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = cmp::min(cmp::min(buf.len(), 1000), self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[test]
+    fn huge_len_does_not_preallocate() {
+        let mut reader = Dribble(b"hello");
+        let bytes = block_on(reader.read_bytes(usize::MAX)).unwrap();
+        assert_eq!(&bytes[..], b"hello");
+        assert!(bytes.capacity() <= MIN_GROWTH);
+
+        let mut reader = Dribble(b"hello");
+        let err = block_on(reader.read_exact_bytes(usize::MAX)).unwrap_err();
+        assert!(matches!(err, ReadExactError::Eof(5)));
+    }
+
+    #[test]
+    fn exact_grows_to_len() {
+        let data: [u8; 3 * MIN_GROWTH] = [7; 3 * MIN_GROWTH];
+        let mut reader = Dribble(&data);
+        let bytes = block_on(reader.read_exact_bytes(2 * MIN_GROWTH + 1)).unwrap();
+        assert_eq!(&bytes[..], &data[..2 * MIN_GROWTH + 1]);
+        assert_eq!(reader.0.len(), MIN_GROWTH - 1);
+    }
+}
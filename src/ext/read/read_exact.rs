@@ -1,16 +1,16 @@
-use crate::AsyncRead;
+use crate::{poll::poll_read_exact, AsyncRead, Classify, ErrorKind};
 
 use _futures::ready;
 use core::{
+    fmt,
     future::Future,
-    marker::Unpin,
     pin::Pin,
     task::{Context, Poll},
 };
 
-pub(crate) fn read_exact<'a, A>(reader: &'a mut A, buf: &'a mut [u8]) -> ReadExact<'a, A>
+pub(crate) fn read_exact<'a, A>(reader: Pin<&'a mut A>, buf: &'a mut [u8]) -> ReadExact<'a, A>
 where
-    A: AsyncRead + Unpin + ?Sized,
+    A: AsyncRead + ?Sized,
 {
     ReadExact {
         reader,
@@ -19,17 +19,65 @@ where
     }
 }
 
+/// Not cancellation safe: if dropped before completing, the bytes already read into `buf`
+/// are lost. Use [`read_exact_resumable`](crate::AsyncReadExt::read_exact_resumable) when the
+/// future may be dropped, for example in a `select!`.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct ReadExact<'a, A: ?Sized> {
-    reader: &'a mut A,
+    reader: Pin<&'a mut A>,
     buf: &'a mut [u8],
     pos: usize,
 }
 
+/// Progress of a [`ReadExactResumable`], kept by the caller so that a dropped future can be
+/// continued by a new one.
+///
+/// The state is reset when the read completes successfully. After an error it keeps the
+/// progress made so far; call [`reset`](ReadExactState::reset) to start over.
+#[derive(Debug, Default, Clone)]
+pub struct ReadExactState {
+    pos: usize,
+}
+
+impl ReadExactState {
+    pub fn new() -> Self {
+        ReadExactState { pos: 0 }
+    }
+
+    /// Number of bytes already read into the buffer.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+pub(crate) fn read_exact_resumable<'a, A>(
+    reader: Pin<&'a mut A>,
+    buf: &'a mut [u8],
+    state: &'a mut ReadExactState,
+) -> ReadExactResumable<'a, A>
+where
+    A: AsyncRead + ?Sized,
+{
+    ReadExactResumable { reader, buf, state }
+}
+
+/// Cancellation safe, provided the next future is given the same buffer and state.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadExactResumable<'a, A: ?Sized> {
+    reader: Pin<&'a mut A>,
+    buf: &'a mut [u8],
+    state: &'a mut ReadExactState,
+}
+
 #[derive(Debug)]
 pub enum ReadExactError<T> {
-    Eof,
+    Eof(usize),
     Read(T),
 }
 
@@ -39,9 +87,39 @@ impl<T> From<T> for ReadExactError<T> {
     }
 }
 
+impl<T: fmt::Display> fmt::Display for ReadExactError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadExactError::Eof(read) => {
+                write!(f, "unexpected end of file after {} bytes", read)
+            }
+            ReadExactError::Read(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::error::Error> std::error::Error for ReadExactError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadExactError::Eof(_) => None,
+            ReadExactError::Read(e) => e.source(),
+        }
+    }
+}
+
+impl<T: Classify> Classify for ReadExactError<T> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ReadExactError::Eof(_) => ErrorKind::UnexpectedEof,
+            ReadExactError::Read(e) => e.kind(),
+        }
+    }
+}
+
 impl<A> Future for ReadExact<'_, A>
 where
-    A: AsyncRead + Unpin + ?Sized,
+    A: AsyncRead + ?Sized,
 {
     type Output = Result<usize, ReadExactError<A::Error>>;
 
@@ -49,19 +127,30 @@ where
         mut self: Pin<&mut Self>,
         cx: &mut Context,
     ) -> Poll<Result<usize, ReadExactError<A::Error>>> {
-        loop {
-            if self.pos < self.buf.len() {
-                let me = &mut *self;
-                let n = ready!(Pin::new(&mut *me.reader).poll_read(cx, &mut me.buf[me.pos..]))?;
-                me.pos += n;
-                if n == 0 {
-                    return Err(ReadExactError::Eof).into();
-                }
-            }
+        let me = &mut *self;
+        ready!(poll_read_exact(me.reader.as_mut(), cx, me.buf, &mut me.pos))?;
+        Poll::Ready(Ok(me.pos))
+    }
+}
 
-            if self.pos >= self.buf.len() {
-                return Poll::Ready(Ok(self.pos));
-            }
-        }
+impl<A> Future for ReadExactResumable<'_, A>
+where
+    A: AsyncRead + ?Sized,
+{
+    type Output = Result<usize, ReadExactError<A::Error>>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<usize, ReadExactError<A::Error>>> {
+        let me = &mut *self;
+        ready!(poll_read_exact(
+            me.reader.as_mut(),
+            cx,
+            me.buf,
+            &mut me.state.pos
+        ))?;
+        me.state.reset();
+        Poll::Ready(Ok(me.buf.len()))
     }
 }
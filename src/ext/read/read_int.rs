@@ -1,17 +1,51 @@
-use crate::AsyncRead;
+use crate::{
+    poll::{self, poll_read_exact},
+    AsyncRead, Classify, ErrorKind, ReadExactError,
+};
 
-use bytes::Buf;
+use _futures::ready;
 use core::{
+    fmt,
     future::Future,
+    marker::PhantomData,
     mem::size_of,
     pin::Pin,
     task::{Context, Poll},
 };
 use pin_project_lite::pin_project;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialInt {
+    buf: [u8; 16],
+    len: u8,
+}
+
+impl PartialInt {
+    pub(crate) fn new(bytes: &[u8]) -> Self {
+        let mut buf = [0; 16];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        PartialInt {
+            buf,
+            len: bytes.len() as u8,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len as usize]
+    }
+}
+
 #[derive(Debug)]
 pub enum ReadIntError<T> {
-    Eof,
+    Eof(PartialInt),
     Read(T),
 }
 
@@ -21,79 +55,172 @@ impl<T> From<T> for ReadIntError<T> {
     }
 }
 
-macro_rules! reader {
-    ($name:ident, $ty:ty, $reader:ident) => {
-        reader!($name, $ty, $reader, size_of::<$ty>());
-    };
-    ($name:ident, $ty:ty, $reader:ident, $bytes:expr) => {
-        pin_project! {
-            pub struct $name<R> {
-                #[pin]
-                src: R,
-                buf: [u8; $bytes],
-                read: u8,
+impl<T: fmt::Display> fmt::Display for ReadIntError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadIntError::Eof(partial) => {
+                write!(f, "unexpected end of file after {} bytes", partial.len())
             }
+            ReadIntError::Read(e) => e.fmt(f),
         }
+    }
+}
 
-        impl<R> $name<R> {
-            pub(crate) fn new(src: R) -> Self {
-                $name {
-                    src,
-                    buf: [0; $bytes],
-                    read: 0,
-                }
-            }
+#[cfg(feature = "std")]
+impl<T: std::error::Error> std::error::Error for ReadIntError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadIntError::Eof(_) => None,
+            ReadIntError::Read(e) => e.source(),
         }
+    }
+}
 
-        impl<R> Future for $name<R>
-        where
-            R: AsyncRead,
-        {
-            type Output = Result<$ty, ReadIntError<R::Error>>;
+impl<T: Classify> Classify for ReadIntError<T> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ReadIntError::Eof(_) => ErrorKind::UnexpectedEof,
+            ReadIntError::Read(e) => e.kind(),
+        }
+    }
+}
 
-            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-                let mut me = self.project();
+/// Progress of a [`ReadIntResumable`] reading a `T`, kept by the caller so that a dropped
+/// future can be continued by a new one.
+///
+/// The state is typed by the integer it reads, so it can't be resumed by a read of a
+/// different width:
+///
+/// ```compile_fail
+/// use core_futures_io::{AsyncReadExt, ReadIntState};
+///
+/// let mut reader = &b"\x01\x02\x03\x04"[..];
+/// let mut state = ReadIntState::<u32>::new();
+/// let _ = reader.read_u16_resumable(&mut state);
+/// ```
+///
+/// It is reset when the read completes successfully; after an error call
+/// [`reset`](ReadIntState::reset) to start over.
+pub struct ReadIntState<T> {
+    buf: [u8; 16],
+    pos: usize,
+    _int: PhantomData<T>,
+}
 
-                if *me.read == $bytes as u8 {
-                    return Poll::Ready(Ok(Buf::$reader(&mut &me.buf[..])));
-                }
+impl<T> ReadIntState<T> {
+    pub fn new() -> Self {
+        ReadIntState {
+            buf: [0; 16],
+            pos: 0,
+            _int: PhantomData,
+        }
+    }
 
-                while *me.read < $bytes as u8 {
-                    *me.read += match me
-                        .src
-                        .as_mut()
-                        .poll_read(cx, &mut me.buf[*me.read as usize..])
-                    {
-                        Poll::Pending => return Poll::Pending,
-                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
-                        Poll::Ready(Ok(0)) => {
-                            return Poll::Ready(Err(ReadIntError::Eof));
-                        }
-                        Poll::Ready(Ok(n)) => n as u8,
-                    };
-                }
+    /// The bytes of the integer read so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.pos]
+    }
 
-                let num = Buf::$reader(&mut &me.buf[..]);
+    pub fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
 
-                Poll::Ready(Ok(num))
+impl<T> Default for ReadIntState<T> {
+    fn default() -> Self {
+        ReadIntState::new()
+    }
+}
+
+impl<T> Clone for ReadIntState<T> {
+    fn clone(&self) -> Self {
+        ReadIntState {
+            buf: self.buf,
+            pos: self.pos,
+            _int: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ReadIntState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadIntState")
+            .field("read", &self.as_bytes())
+            .finish()
+    }
+}
+
+pub(crate) fn read_int_resumable<'a, R, T>(
+    reader: Pin<&'a mut R>,
+    state: &'a mut ReadIntState<T>,
+    get: fn(&mut &[u8]) -> T,
+) -> ReadIntResumable<'a, R, T>
+where
+    R: AsyncRead + ?Sized,
+{
+    ReadIntResumable { reader, state, get }
+}
+
+/// Cancellation safe, provided the next future is given the same state.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadIntResumable<'a, R: ?Sized, T> {
+    reader: Pin<&'a mut R>,
+    state: &'a mut ReadIntState<T>,
+    get: fn(&mut &[u8]) -> T,
+}
+
+impl<R: ?Sized + fmt::Debug, T> fmt::Debug for ReadIntResumable<'_, R, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadIntResumable")
+            .field("reader", &self.reader)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<R, T> Future for ReadIntResumable<'_, R, T>
+where
+    R: AsyncRead + ?Sized,
+{
+    type Output = Result<T, ReadIntError<R::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        let state = &mut *me.state;
+        let buf = &mut state.buf[..size_of::<T>()];
+        match ready!(poll_read_exact(me.reader.as_mut(), cx, buf, &mut state.pos)) {
+            Ok(()) => {
+                state.pos = 0;
+                Poll::Ready(Ok((me.get)(&mut &buf[..])))
+            }
+            Err(ReadExactError::Eof(n)) => {
+                Poll::Ready(Err(ReadIntError::Eof(PartialInt::new(&buf[..n]))))
             }
+            Err(ReadExactError::Read(e)) => Poll::Ready(Err(ReadIntError::Read(e))),
         }
-    };
+    }
 }
 
-macro_rules! reader8 {
-    ($name:ident, $ty:ty) => {
+macro_rules! reader {
+    ($name:ident, $ty:ty, $poll:ident) => {
         pin_project! {
-            #[doc(hidden)]
+            /// Not cancellation safe: if dropped before completing, the bytes already read are
+            /// lost. Use the `_resumable` variant when the future may be dropped.
             pub struct $name<R> {
                 #[pin]
-                reader: R,
+                src: R,
+                buf: [u8; size_of::<$ty>()],
+                read: usize,
             }
         }
 
         impl<R> $name<R> {
-            pub(crate) fn new(reader: R) -> $name<R> {
-                $name { reader }
+            pub(crate) fn new(src: R) -> Self {
+                $name {
+                    src,
+                    buf: [0; size_of::<$ty>()],
+                    read: 0,
+                }
             }
         }
 
@@ -105,29 +232,85 @@ macro_rules! reader8 {
 
             fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
                 let me = self.project();
-
-                let mut buf = [0; 1];
-                match me.reader.poll_read(cx, &mut buf[..]) {
-                    Poll::Pending => Poll::Pending,
-                    Poll::Ready(Err(e)) => Poll::Ready(Err(e.into())),
-                    Poll::Ready(Ok(0)) => Poll::Ready(Err(ReadIntError::Eof)),
-                    Poll::Ready(Ok(1)) => Poll::Ready(Ok(buf[0] as $ty)),
-                    Poll::Ready(Ok(_)) => unreachable!(),
-                }
+                poll::$poll(me.src, cx, me.buf, me.read)
             }
         }
     };
 }
 
-reader8!(ReadU8, u8);
-reader8!(ReadI8, i8);
+reader!(ReadU8, u8, poll_read_u8);
+reader!(ReadI8, i8, poll_read_i8);
+
+reader!(ReadU16, u16, poll_read_u16_be);
+reader!(ReadU32, u32, poll_read_u32_be);
+reader!(ReadU64, u64, poll_read_u64_be);
+reader!(ReadU128, u128, poll_read_u128_be);
 
-reader!(ReadU16, u16, get_u16);
-reader!(ReadU32, u32, get_u32);
-reader!(ReadU64, u64, get_u64);
-reader!(ReadU128, u128, get_u128);
+reader!(ReadI16, i16, poll_read_i16_be);
+reader!(ReadI32, i32, poll_read_i32_be);
+reader!(ReadI64, i64, poll_read_i64_be);
+reader!(ReadI128, i128, poll_read_i128_be);
 
-reader!(ReadI16, i16, get_i16);
-reader!(ReadI32, i32, get_i32);
-reader!(ReadI64, i64, get_i64);
-reader!(ReadI128, i128, get_i128);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AsyncReadExt;
+    use _futures::task::noop_waker_ref;
+
+    /// Hands out `data` one chunk per read and is pending once it runs out.
+    struct Chunks<'a>(&'a [&'a [u8]]);
+
+    impl AsyncRead for Chunks<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            match self.0.split_first() {
+                Some((chunk, rest)) => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    self.0 = rest;
+                    Poll::Ready(Ok(chunk.len()))
+                }
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    fn poll_once<F: Future + Unpin>(mut fut: F) -> Poll<F::Output> {
+        Pin::new(&mut fut).poll(&mut Context::from_waker(noop_waker_ref()))
+    }
+
+    #[test]
+    fn resume_after_drop() {
+        let mut state = ReadIntState::new();
+        let mut reader = Chunks(&[&[1, 2, 3]]);
+        assert!(poll_once(reader.read_u32_resumable(&mut state)).is_pending());
+        assert_eq!(state.as_bytes(), &[1, 2, 3]);
+
+        reader.0 = &[&[4]];
+        let value = poll_once(reader.read_u32_resumable(&mut state));
+        assert!(matches!(value, Poll::Ready(Ok(0x0102_0304))));
+        assert!(state.as_bytes().is_empty());
+
+        reader.0 = &[&[5, 6, 7, 8]];
+        let value = poll_once(reader.read_u32_resumable(&mut state));
+        assert!(matches!(value, Poll::Ready(Ok(0x0506_0708))));
+    }
+
+    #[test]
+    fn reset_after_eof() {
+        let mut state = ReadIntState::new();
+        let mut reader = Chunks(&[&[1], &[]]);
+        match poll_once(reader.read_u16_resumable(&mut state)) {
+            Poll::Ready(Err(ReadIntError::Eof(partial))) => assert_eq!(partial.as_bytes(), &[1]),
+            other => panic!("expected EOF, got {:?}", other),
+        }
+        state.reset();
+        reader.0 = &[&[2, 3]];
+        let value = poll_once(reader.read_u16_resumable(&mut state));
+        assert!(matches!(value, Poll::Ready(Ok(0x0203))));
+    }
+}
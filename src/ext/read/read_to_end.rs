@@ -1,46 +1,116 @@
-use crate::AsyncRead;
+use crate::{AsyncRead, BorrowedBuf, Classify, ErrorKind};
 
 use _futures::ready;
 use alloc::vec::Vec;
 use core::{
+    cmp, fmt,
     future::Future,
-    mem::MaybeUninit,
     pin::Pin,
     task::{Context, Poll},
 };
 
+const MIN_GROWTH: usize = 8 * 1024;
+const PROBE_SIZE: usize = 32;
+/// Upper bound on what a reader's `size_hint` can make us allocate ahead of the data.
+const MAX_PRESIZE: usize = 4 * 1024 * 1024;
+
+/// Cancellation safe: if dropped before completing, the bytes read so far are left in `buf`.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 #[cfg_attr(docsrs, doc(cfg(feature = "io-util")))]
 pub struct ReadToEnd<'a, R: ?Sized> {
-    reader: &'a mut R,
+    reader: Pin<&'a mut R>,
+    buf: &'a mut Vec<u8>,
+    start_len: usize,
+    initialized: usize,
+}
+
+/// Cancellation safe: if dropped before completing, the bytes read so far are left in `buf`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadToEndLimited<'a, R: ?Sized> {
+    reader: Pin<&'a mut R>,
     buf: &'a mut Vec<u8>,
     start_len: usize,
+    initialized: usize,
+    limit: usize,
+}
+
+#[derive(Debug)]
+pub enum ReadToEndError<T> {
+    Read(T),
+    TooLarge(usize),
+}
+
+impl<T: fmt::Display> fmt::Display for ReadToEndError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadToEndError::Read(e) => e.fmt(f),
+            ReadToEndError::TooLarge(limit) => {
+                write!(f, "stream exceeded the size limit of {} bytes", limit)
+            }
+        }
+    }
 }
 
-pub(crate) fn read_to_end<'a, R>(reader: &'a mut R, buf: &'a mut Vec<u8>) -> ReadToEnd<'a, R>
+#[cfg(feature = "std")]
+impl<T: std::error::Error> std::error::Error for ReadToEndError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadToEndError::Read(e) => e.source(),
+            ReadToEndError::TooLarge(_) => None,
+        }
+    }
+}
+
+impl<T: Classify> Classify for ReadToEndError<T> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ReadToEndError::Read(e) => e.kind(),
+            ReadToEndError::TooLarge(_) => ErrorKind::InvalidData,
+        }
+    }
+}
+
+pub(crate) fn read_to_end<'a, R>(reader: Pin<&'a mut R>, buf: &'a mut Vec<u8>) -> ReadToEnd<'a, R>
 where
-    R: AsyncRead + Unpin + ?Sized,
+    R: AsyncRead + ?Sized,
 {
+    presize(buf, reader.size_hint(), None);
     let start_len = buf.len();
     ReadToEnd {
         reader,
         buf,
         start_len,
+        initialized: 0,
     }
 }
 
-struct Guard<'a> {
+pub(crate) fn read_to_end_limited<'a, R>(
+    reader: Pin<&'a mut R>,
     buf: &'a mut Vec<u8>,
-    len: usize,
+    limit: usize,
+) -> ReadToEndLimited<'a, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    presize(buf, reader.size_hint(), Some(limit));
+    let start_len = buf.len();
+    ReadToEndLimited {
+        reader,
+        buf,
+        start_len,
+        initialized: 0,
+        limit,
+    }
 }
 
-impl Drop for Guard<'_> {
-    fn drop(&mut self) {
-        unsafe {
-            self.buf.set_len(self.len);
-        }
+pub(super) fn presize(buf: &mut Vec<u8>, hint: (usize, Option<usize>), limit: Option<usize>) {
+    let mut additional = cmp::min(hint.0, MAX_PRESIZE);
+    if let Some(limit) = limit {
+        additional = cmp::min(additional, limit);
     }
+    buf.reserve(additional);
 }
 
 pub(super) fn read_to_end_internal<R: AsyncRead + ?Sized>(
@@ -48,49 +118,295 @@ pub(super) fn read_to_end_internal<R: AsyncRead + ?Sized>(
     cx: &mut Context,
     buf: &mut Vec<u8>,
     start_len: usize,
-) -> Poll<Result<usize, R::Error>> {
-    let mut g = Guard {
-        len: buf.len(),
-        buf,
-    };
-    let ret;
+    initialized: &mut usize,
+    limit: Option<usize>,
+) -> Poll<Result<usize, ReadToEndError<R::Error>>> {
     loop {
-        if g.len == g.buf.len() {
-            unsafe {
-                g.buf.reserve(32);
-                let capacity = g.buf.capacity();
-                g.buf.set_len(capacity);
+        let read = buf.len() - start_len;
+        let remaining = limit.map(|limit| limit - read);
 
-                let b = &mut *(&mut g.buf[g.len..] as *mut [u8] as *mut [MaybeUninit<u8>]);
+        if buf.len() == buf.capacity() || remaining == Some(0) {
+            let (lower, upper) = rd.size_hint();
 
-                rd.prepare_uninitialized_buffer(b);
+            if upper == Some(0) || remaining == Some(0) {
+                let mut probe = [0; PROBE_SIZE];
+                let len = cmp::min(PROBE_SIZE, remaining.unwrap_or(PROBE_SIZE).max(1));
+                let n = ready!(rd.as_mut().poll_read(cx, &mut probe[..len]))
+                    .map_err(ReadToEndError::Read)?;
+                if n == 0 {
+                    return Poll::Ready(Ok(read));
+                }
+                if let (Some(limit), Some(remaining)) = (limit, remaining) {
+                    if n > remaining {
+                        return Poll::Ready(Err(ReadToEndError::TooLarge(limit)));
+                    }
+                }
+                buf.extend_from_slice(&probe[..n]);
+                continue;
             }
-        }
 
-        match ready!(rd.as_mut().poll_read(cx, &mut g.buf[g.len..])) {
-            Ok(0) => {
-                ret = Poll::Ready(Ok(g.len - start_len));
-                break;
-            }
-            Ok(n) => g.len += n,
-            Err(e) => {
-                ret = Poll::Ready(Err(e));
-                break;
+            let lower = cmp::min(lower, MAX_PRESIZE);
+            let mut additional = cmp::max(cmp::max(lower, buf.capacity()), MIN_GROWTH);
+            if let Some(remaining) = remaining {
+                additional = cmp::min(additional, remaining);
             }
+            buf.reserve(additional);
+            // Growing may move the allocation, and only the filled part is carried over.
+            *initialized = 0;
+        }
+
+        let spare = buf.spare_capacity_mut();
+        let len = match remaining {
+            Some(remaining) => cmp::min(spare.len(), remaining),
+            None => spare.len(),
+        };
+        let mut b = BorrowedBuf::uninit(&mut spare[..len]);
+        unsafe {
+            b.assume_init(cmp::min(*initialized, len));
         }
-    }
 
-    ret
+        ready!(rd.as_mut().poll_read_uninit(cx, b.unfilled())).map_err(ReadToEndError::Read)?;
+
+        let n = b.filled().len();
+        if n == 0 {
+            return Poll::Ready(Ok(read));
+        }
+        *initialized = cmp::max(*initialized, b.initialized().len()) - n;
+
+        unsafe {
+            buf.set_len(buf.len() + n);
+        }
+    }
 }
 
 impl<A> Future for ReadToEnd<'_, A>
 where
-    A: AsyncRead + ?Sized + Unpin,
+    A: AsyncRead + ?Sized,
 {
     type Output = Result<usize, A::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         let this = &mut *self;
-        read_to_end_internal(Pin::new(&mut this.reader), cx, this.buf, this.start_len)
+        read_to_end_internal(
+            this.reader.as_mut(),
+            cx,
+            this.buf,
+            this.start_len,
+            &mut this.initialized,
+            None,
+        )
+        .map_err(|e| match e {
+            ReadToEndError::Read(e) => e,
+            ReadToEndError::TooLarge(_) => unreachable!(),
+        })
+    }
+}
+
+impl<A> Future for ReadToEndLimited<'_, A>
+where
+    A: AsyncRead + ?Sized,
+{
+    type Output = Result<usize, ReadToEndError<A::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        read_to_end_internal(
+            this.reader.as_mut(),
+            cx,
+            this.buf,
+            this.start_len,
+            &mut this.initialized,
+            Some(this.limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt, BorrowedCursor};
+    use alloc::vec;
+
+    /// Hands out at most `chunk` bytes per read and initializes more of the buffer than it
+    /// fills, so the initialized-length bookkeeping is exercised.
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+        exact: bool,
+    }
+
+    impl<'a> Chunked<'a> {
+        fn new(data: &'a [u8], chunk: usize) -> Self {
+            Chunked {
+                data,
+                chunk,
+                exact: false,
+            }
+        }
+    }
+
+    impl AsyncRead for Chunked<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = cmp::min(cmp::min(self.chunk, buf.len()), self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_read_uninit(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            mut buf: BorrowedCursor<'_>,
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = cmp::min(cmp::min(self.chunk, buf.remaining()), self.data.len());
+            let extra = cmp::min(buf.remaining(), n + 16);
+            let unfilled = buf.initialize_unfilled_to(extra);
+            unfilled[..n].copy_from_slice(&self.data[..n]);
+            for byte in &mut unfilled[n..] {
+                *byte = 0xAA;
+            }
+            buf.advance(n);
+            self.data = &self.data[n..];
+            Poll::Ready(Ok(n))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            if self.exact {
+                (self.data.len(), Some(self.data.len()))
+            } else {
+                (0, None)
+            }
+        }
+    }
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn short_reads() {
+        let data = pattern(1000);
+        let mut reader = Chunked::new(&data, 7);
+        let mut buf = Vec::new();
+        let n = block_on(reader.read_to_end(&mut buf)).unwrap();
+        assert_eq!(n, 1000);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    fn regrowth_keeps_existing_data() {
+        let data = pattern(3 * MIN_GROWTH + 5);
+        let mut reader = Chunked::new(&data, 1500);
+        let mut buf = vec![1, 2, 3];
+        buf.shrink_to_fit();
+        let n = block_on(reader.read_to_end(&mut buf)).unwrap();
+        assert_eq!(n, data.len());
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(&buf[3..], &data[..]);
+    }
+
+    #[test]
+    fn exact_size_hint_ends_with_probe() {
+        let data = pattern(100);
+        let mut reader = Chunked::new(&data, 64);
+        reader.exact = true;
+        let mut buf = Vec::new();
+        block_on(reader.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, data);
+        assert!(buf.capacity() < MIN_GROWTH);
+    }
+
+    #[test]
+    fn huge_size_hint_is_capped() {
+        struct Liar;
+
+        impl AsyncRead for Liar {
+            type Error = void::Void;
+
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+                _: &mut [u8],
+            ) -> Poll<Result<usize, Self::Error>> {
+                Poll::Ready(Ok(0))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (usize::MAX, None)
+            }
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(block_on(Liar.read_to_end(&mut buf)).unwrap(), 0);
+        assert!(buf.capacity() <= MAX_PRESIZE);
+    }
+
+    #[test]
+    fn reader_cannot_fake_progress() {
+        /// Fills a buffer of its own instead of the one it was handed and over-reports.
+        struct Evil;
+
+        impl AsyncRead for Evil {
+            type Error = void::Void;
+
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+                _: &mut [u8],
+            ) -> Poll<Result<usize, Self::Error>> {
+                Poll::Ready(Ok(usize::MAX))
+            }
+
+            fn poll_read_uninit(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+                _: BorrowedCursor<'_>,
+            ) -> Poll<Result<usize, Self::Error>> {
+                let mut storage = [0; 64];
+                let mut own = BorrowedBuf::new(&mut storage);
+                own.unfilled().advance(64);
+                Poll::Ready(Ok(usize::MAX))
+            }
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(block_on(Evil.read_to_end(&mut buf)).unwrap(), 0);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn limit_truncates_and_reports() {
+        let data = pattern(50);
+        let mut reader = Chunked::new(&data, 8);
+        let mut buf = Vec::new();
+        let err = block_on(reader.read_to_end_limited(&mut buf, 20)).unwrap_err();
+        assert!(matches!(err, ReadToEndError::TooLarge(20)));
+        assert_eq!(buf, &data[..20]);
+    }
+
+    #[test]
+    fn limit_matching_length_succeeds() {
+        let data = pattern(20);
+        let mut reader = Chunked::new(&data, 8);
+        let mut buf = Vec::new();
+        let n = block_on(reader.read_to_end_limited(&mut buf, 20)).unwrap();
+        assert_eq!(n, 20);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    fn limit_counts_only_new_bytes() {
+        let data = pattern(10);
+        let mut reader = Chunked::new(&data, 3);
+        let mut buf = vec![0; 100];
+        let n = block_on(reader.read_to_end_limited(&mut buf, 10)).unwrap();
+        assert_eq!(n, 10);
+        assert_eq!(&buf[100..], &data[..]);
     }
 }
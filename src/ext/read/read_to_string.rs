@@ -1,5 +1,5 @@
-use super::read_to_end::read_to_end_internal;
-use crate::AsyncRead;
+use super::read_to_end::{presize, read_to_end_internal, ReadToEndError};
+use crate::{AsyncRead, Classify, ErrorKind};
 use _futures::ready;
 use alloc::{
     str::{self, Utf8Error},
@@ -7,71 +7,374 @@ use alloc::{
     vec::Vec,
 };
 use core::{
+    fmt,
     future::Future,
     mem,
     pin::Pin,
     task::{Context, Poll},
 };
 
+/// Not cancellation safe: if dropped before completing, the valid UTF-8 read so far is left
+/// in `buf`, but a trailing incomplete character is lost.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct ReadToString<'a, R: ?Sized> {
-    reader: &'a mut R,
+    reader: Pin<&'a mut R>,
     buf: &'a mut String,
     bytes: Vec<u8>,
     start_len: usize,
+    initialized: usize,
+    validated: usize,
+    limit: Option<usize>,
+    done: bool,
+}
+
+#[derive(Debug)]
+pub struct InvalidUtf8 {
+    bytes: Vec<u8>,
+    error: Utf8Error,
+}
+
+impl InvalidUtf8 {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn valid_up_to(&self) -> usize {
+        self.error.valid_up_to()
+    }
+
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl fmt::Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stream did not contain valid UTF-8: {}", self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidUtf8 {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
 }
 
 #[derive(Debug)]
 pub enum ReadToStringError<T> {
     Read(T),
-    Utf8(Utf8Error),
+    Utf8(InvalidUtf8),
+    TooLarge(usize),
+}
+
+impl<T: fmt::Display> fmt::Display for ReadToStringError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadToStringError::Read(e) => e.fmt(f),
+            ReadToStringError::Utf8(e) => e.fmt(f),
+            ReadToStringError::TooLarge(limit) => {
+                write!(f, "stream exceeded the size limit of {} bytes", limit)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::error::Error> std::error::Error for ReadToStringError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadToStringError::Read(e) => e.source(),
+            ReadToStringError::Utf8(e) => e.source(),
+            ReadToStringError::TooLarge(_) => None,
+        }
+    }
+}
+
+impl<T: Classify> Classify for ReadToStringError<T> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ReadToStringError::Read(e) => e.kind(),
+            ReadToStringError::Utf8(_) | ReadToStringError::TooLarge(_) => ErrorKind::InvalidData,
+        }
+    }
+}
+
+pub(crate) fn read_to_string<'a, R>(
+    reader: Pin<&'a mut R>,
+    buf: &'a mut String,
+) -> ReadToString<'a, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    read_to_string_with(reader, buf, None)
+}
+
+pub(crate) fn read_to_string_limited<'a, R>(
+    reader: Pin<&'a mut R>,
+    buf: &'a mut String,
+    limit: usize,
+) -> ReadToString<'a, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    read_to_string_with(reader, buf, Some(limit))
 }
 
-pub(crate) fn read_to_string<'a, R>(reader: &'a mut R, buf: &'a mut String) -> ReadToString<'a, R>
+fn read_to_string_with<'a, R>(
+    reader: Pin<&'a mut R>,
+    buf: &'a mut String,
+    limit: Option<usize>,
+) -> ReadToString<'a, R>
 where
-    R: AsyncRead + ?Sized + Unpin,
+    R: AsyncRead + ?Sized,
 {
-    let start_len = buf.len();
+    let mut bytes = unsafe { mem::take(buf.as_mut_vec()) };
+    presize(&mut bytes, reader.size_hint(), limit);
+    let start_len = bytes.len();
     ReadToString {
         reader,
-        bytes: unsafe { mem::replace(buf.as_mut_vec(), Vec::new()) },
+        bytes,
         buf,
         start_len,
-    }
-}
-
-fn read_to_string_internal<R: AsyncRead + ?Sized>(
-    reader: Pin<&mut R>,
-    cx: &mut Context,
-    buf: &mut String,
-    bytes: &mut Vec<u8>,
-    start_len: usize,
-) -> Poll<Result<usize, ReadToStringError<R::Error>>> {
-    let ret =
-        ready!(read_to_end_internal(reader, cx, bytes, start_len)).map_err(ReadToStringError::Read);
-    if let Err(e) = str::from_utf8(&bytes) {
-        Poll::Ready(ret.and_then(|_| Err(ReadToStringError::Utf8(e))))
-    } else {
-        debug_assert!(buf.is_empty());
-        mem::swap(unsafe { buf.as_mut_vec() }, bytes);
-        Poll::Ready(ret)
+        initialized: 0,
+        validated: start_len,
+        limit,
+        done: false,
     }
 }
 
 impl<A> Future for ReadToString<'_, A>
 where
-    A: AsyncRead + ?Sized + Unpin,
+    A: AsyncRead + ?Sized,
 {
     type Output = Result<usize, ReadToStringError<A::Error>>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        let Self {
-            reader,
-            buf,
-            bytes,
-            start_len,
-        } = &mut *self;
-        read_to_string_internal(Pin::new(reader), cx, buf, bytes, *start_len)
+        let me = &mut *self;
+        let ret = read_to_end_internal(
+            me.reader.as_mut(),
+            cx,
+            &mut me.bytes,
+            me.start_len,
+            &mut me.initialized,
+            me.limit,
+        );
+
+        let complete = match str::from_utf8(&me.bytes[me.validated..]) {
+            Ok(_) => {
+                me.validated = me.bytes.len();
+                true
+            }
+            Err(e) => {
+                me.validated += e.valid_up_to();
+                if e.error_len().is_some() {
+                    return Poll::Ready(Err(me.invalid()));
+                }
+                false
+            }
+        };
+
+        match ready!(ret) {
+            Ok(_) if !complete => Poll::Ready(Err(me.invalid())),
+            Ok(n) => {
+                mem::swap(unsafe { me.buf.as_mut_vec() }, &mut me.bytes);
+                me.done = true;
+                Poll::Ready(Ok(n))
+            }
+            Err(e) => {
+                me.bytes.truncate(me.validated);
+                mem::swap(unsafe { me.buf.as_mut_vec() }, &mut me.bytes);
+                me.done = true;
+                Poll::Ready(Err(match e {
+                    ReadToEndError::Read(e) => ReadToStringError::Read(e),
+                    ReadToEndError::TooLarge(limit) => ReadToStringError::TooLarge(limit),
+                }))
+            }
+        }
+    }
+}
+
+impl<R: ?Sized> Drop for ReadToString<'_, R> {
+    fn drop(&mut self) {
+        // Dropped before completing: hand the validated bytes back instead of leaving `buf`
+        // empty.
+        if !self.done {
+            self.bytes.truncate(self.validated);
+            mem::swap(unsafe { self.buf.as_mut_vec() }, &mut self.bytes);
+        }
+    }
+}
+
+impl<R: ?Sized> ReadToString<'_, R> {
+    fn invalid<E>(&mut self) -> ReadToStringError<E> {
+        let bytes = self.bytes.split_off(self.start_len);
+        mem::swap(unsafe { self.buf.as_mut_vec() }, &mut self.bytes);
+        self.done = true;
+        let error = str::from_utf8(&bytes).unwrap_err();
+        ReadToStringError::Utf8(InvalidUtf8 { bytes, error })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt};
+    use alloc::vec::Vec;
+
+    /// Hands out one scripted chunk per read, then either EOF or `Pending` forever.
+    struct Chunks<'a> {
+        chunks: Vec<&'a [u8]>,
+        pending: bool,
+    }
+
+    fn chunks<'a>(chunks: &[&'a [u8]]) -> Chunks<'a> {
+        Chunks {
+            chunks: chunks.iter().rev().copied().collect(),
+            pending: false,
+        }
+    }
+
+    impl AsyncRead for Chunks<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            match self.chunks.pop() {
+                Some(chunk) => {
+                    let n = chunk.len().min(buf.len());
+                    buf[..n].copy_from_slice(&chunk[..n]);
+                    if n < chunk.len() {
+                        self.chunks.push(&chunk[n..]);
+                    }
+                    Poll::Ready(Ok(n))
+                }
+                None if self.pending => Poll::Pending,
+                None => Poll::Ready(Ok(0)),
+            }
+        }
+    }
+
+    fn noop_cx() -> Context<'static> {
+        Context::from_waker(_futures::task::noop_waker_ref())
+    }
+
+    #[test]
+    fn code_point_split_across_reads() {
+        let mut reader = chunks(&[b"a\xc3", b"\xa9\xe2\x82", b"\xac"]);
+        let mut buf = String::from(">");
+        let n = block_on(reader.read_to_string(&mut buf)).unwrap();
+        assert_eq!(n, 6);
+        assert_eq!(buf, ">a\u{e9}\u{20ac}");
+    }
+
+    #[test]
+    fn invalid_utf8_carries_the_bytes_read() {
+        let mut reader = chunks(&[b"ab", b"\xffcd"]);
+        let mut buf = String::from(">");
+        let err = match block_on(reader.read_to_string(&mut buf)) {
+            Err(ReadToStringError::Utf8(e)) => e,
+            other => panic!("expected a UTF-8 error, got {:?}", other),
+        };
+        assert_eq!(err.as_bytes(), b"ab\xffcd");
+        assert_eq!(err.valid_up_to(), 2);
+        assert_eq!(err.utf8_error().error_len(), Some(1));
+        assert_eq!(err.into_bytes(), b"ab\xffcd");
+        assert_eq!(buf, ">");
+    }
+
+    #[test]
+    fn truncated_code_point_at_eof_is_an_error() {
+        let mut reader = chunks(&[b"ab\xe2\x82"]);
+        let mut buf = String::new();
+        let err = match block_on(reader.read_to_string(&mut buf)) {
+            Err(ReadToStringError::Utf8(e)) => e,
+            other => panic!("expected a UTF-8 error, got {:?}", other),
+        };
+        assert_eq!(err.as_bytes(), b"ab\xe2\x82");
+        assert_eq!(err.valid_up_to(), 2);
+        assert_eq!(err.utf8_error().error_len(), None);
+        assert_eq!(buf, "");
+    }
+
+    #[test]
+    fn limited_read() {
+        let mut reader = chunks(&[b"abc"]);
+        let mut buf = String::new();
+        assert_eq!(
+            block_on(reader.read_to_string_limited(&mut buf, 3)).unwrap(),
+            3
+        );
+        assert_eq!(buf, "abc");
+
+        let mut reader = chunks(&[b"ab", b"cd"]);
+        let mut buf = String::from(">");
+        match block_on(reader.read_to_string_limited(&mut buf, 3)) {
+            Err(ReadToStringError::TooLarge(3)) => {}
+            other => panic!("expected TooLarge(3), got {:?}", other),
+        }
+        assert_eq!(buf, ">abc");
+
+        // A limit that cuts a code point in half keeps only the validated prefix.
+        let mut reader = chunks(&["a\u{20ac}".as_bytes()]);
+        let mut buf = String::new();
+        match block_on(reader.read_to_string_limited(&mut buf, 2)) {
+            Err(ReadToStringError::TooLarge(2)) => {}
+            other => panic!("expected TooLarge(2), got {:?}", other),
+        }
+        assert_eq!(buf, "a");
+    }
+
+    #[test]
+    fn drop_before_completion_keeps_validated_text() {
+        let mut reader = chunks(&[b"h\xc3"]);
+        reader.pending = true;
+        let mut buf = String::from(">");
+        {
+            let mut fut = reader.read_to_string(&mut buf);
+            assert!(Pin::new(&mut fut).poll(&mut noop_cx()).is_pending());
+        }
+        assert_eq!(buf, ">h");
+    }
+
+    #[test]
+    fn drop_without_polling_keeps_buf() {
+        let mut reader = chunks(&[b"abc"]);
+        let mut buf = String::from(">");
+        drop(reader.read_to_string(&mut buf));
+        assert_eq!(buf, ">");
+    }
+
+    #[test]
+    fn drop_after_completion_keeps_result() {
+        let mut reader = chunks(&[]);
+        let mut buf = String::new();
+        {
+            let mut fut = reader.read_to_string(&mut buf);
+            match Pin::new(&mut fut).poll(&mut noop_cx()) {
+                Poll::Ready(Ok(0)) => {}
+                other => panic!("expected Ready(Ok(0)), got {:?}", other),
+            }
+        }
+        assert_eq!(buf, "");
+
+        let mut reader = chunks(&[b"\xff"]);
+        let mut buf = String::new();
+        {
+            let mut fut = reader.read_to_string(&mut buf);
+            assert!(matches!(
+                Pin::new(&mut fut).poll(&mut noop_cx()),
+                Poll::Ready(Err(ReadToStringError::Utf8(_)))
+            ));
+        }
+        assert_eq!(buf, "");
     }
 }
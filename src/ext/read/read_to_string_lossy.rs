@@ -0,0 +1,169 @@
+use super::read_to_end::{presize, read_to_end_internal, ReadToEndError};
+use crate::AsyncRead;
+use _futures::ready;
+use alloc::{str, string::String, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Not cancellation safe: if dropped before completing, the text decoded so far is left in
+/// `buf`, but a trailing incomplete character is lost.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadToStringLossy<'a, R: ?Sized> {
+    reader: Pin<&'a mut R>,
+    buf: &'a mut String,
+    bytes: Vec<u8>,
+    initialized: usize,
+    decoded: usize,
+}
+
+pub(crate) fn read_to_string_lossy<'a, R>(
+    reader: Pin<&'a mut R>,
+    buf: &'a mut String,
+) -> ReadToStringLossy<'a, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    let mut bytes = Vec::new();
+    presize(&mut bytes, reader.size_hint(), None);
+    buf.reserve(bytes.capacity());
+    ReadToStringLossy {
+        reader,
+        buf,
+        bytes,
+        initialized: 0,
+        decoded: 0,
+    }
+}
+
+fn decode(buf: &mut String, bytes: &mut Vec<u8>, eof: bool) -> usize {
+    let mut input = &bytes[..];
+    loop {
+        match str::from_utf8(input) {
+            Ok(valid) => {
+                buf.push_str(valid);
+                input = &[];
+                break;
+            }
+            Err(e) => {
+                let (valid, rest) = input.split_at(e.valid_up_to());
+                buf.push_str(unsafe { str::from_utf8_unchecked(valid) });
+                match e.error_len() {
+                    Some(len) => {
+                        buf.push(char::REPLACEMENT_CHARACTER);
+                        input = &rest[len..];
+                    }
+                    None if eof => {
+                        buf.push(char::REPLACEMENT_CHARACTER);
+                        input = &[];
+                        break;
+                    }
+                    None => {
+                        input = rest;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    let consumed = bytes.len() - input.len();
+    bytes.drain(..consumed);
+    consumed
+}
+
+impl<A> Future for ReadToStringLossy<'_, A>
+where
+    A: AsyncRead + ?Sized,
+{
+    type Output = Result<usize, A::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        let ret = read_to_end_internal(
+            me.reader.as_mut(),
+            cx,
+            &mut me.bytes,
+            0,
+            &mut me.initialized,
+            None,
+        );
+
+        let eof = matches!(ret, Poll::Ready(Ok(_)));
+        let consumed = decode(me.buf, &mut me.bytes, eof);
+        if consumed > 0 {
+            me.decoded += consumed;
+            me.initialized = 0;
+        }
+
+        match ready!(ret) {
+            Ok(_) => Poll::Ready(Ok(me.decoded)),
+            Err(ReadToEndError::Read(e)) => Poll::Ready(Err(e)),
+            Err(ReadToEndError::TooLarge(_)) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt};
+
+    /// Hands out one scripted chunk per read, then EOF.
+    struct Chunks<'a>(Vec<&'a [u8]>);
+
+    fn chunks<'a>(chunks: &[&'a [u8]]) -> Chunks<'a> {
+        Chunks(chunks.iter().rev().copied().collect())
+    }
+
+    impl AsyncRead for Chunks<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            match self.0.pop() {
+                Some(chunk) => {
+                    let n = chunk.len().min(buf.len());
+                    buf[..n].copy_from_slice(&chunk[..n]);
+                    if n < chunk.len() {
+                        self.0.push(&chunk[n..]);
+                    }
+                    Poll::Ready(Ok(n))
+                }
+                None => Poll::Ready(Ok(0)),
+            }
+        }
+    }
+
+    #[test]
+    fn code_point_split_across_reads() {
+        let mut reader = chunks(&[b"a\xc3", b"\xa9\xe2\x82", b"\xac"]);
+        let mut buf = String::from(">");
+        let n = block_on(reader.read_to_string_lossy(&mut buf)).unwrap();
+        assert_eq!(n, 6);
+        assert_eq!(buf, ">a\u{e9}\u{20ac}");
+    }
+
+    #[test]
+    fn invalid_bytes_are_replaced() {
+        let mut reader = chunks(&[b"ab", b"\xffcd"]);
+        let mut buf = String::new();
+        let n = block_on(reader.read_to_string_lossy(&mut buf)).unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(buf, "ab\u{fffd}cd");
+    }
+
+    #[test]
+    fn truncated_code_point_at_eof_is_replaced() {
+        let mut reader = chunks(&[b"ab\xe2", b"\x82"]);
+        let mut buf = String::new();
+        let n = block_on(reader.read_to_string_lossy(&mut buf)).unwrap();
+        assert_eq!(n, 4);
+        assert_eq!(buf, "ab\u{fffd}");
+    }
+}
@@ -0,0 +1,32 @@
+use crate::AsyncRead;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Cancellation safe: no data is read while waiting.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Readable<'a, R: ?Sized> {
+    reader: Pin<&'a mut R>,
+}
+
+pub(crate) fn readable<R>(reader: Pin<&mut R>) -> Readable<'_, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    Readable { reader }
+}
+
+impl<R> Future for Readable<'_, R>
+where
+    R: AsyncRead + ?Sized,
+{
+    type Output = Result<(), R::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        me.reader.as_mut().poll_read_ready(cx)
+    }
+}
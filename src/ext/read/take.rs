@@ -1,8 +1,9 @@
-use crate::AsyncRead;
+use crate::{AsyncRead, BorrowedBuf, BorrowedCursor};
 
 use _futures::ready;
 use core::{
-    mem::MaybeUninit,
+    cmp,
+    convert::TryFrom,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -55,10 +56,6 @@ impl<R: AsyncRead> Take<R> {
 impl<R: AsyncRead> AsyncRead for Take<R> {
     type Error = R::Error;
 
-    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [MaybeUninit<u8>]) -> bool {
-        self.inner.prepare_uninitialized_buffer(buf)
-    }
-
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context,
@@ -69,9 +66,162 @@ impl<R: AsyncRead> AsyncRead for Take<R> {
         }
 
         let me = self.project();
-        let max = core::cmp::min(buf.len() as u64, *me.limit_) as usize;
+        let max = cmp::min(buf.len() as u64, *me.limit_) as usize;
         let n = ready!(me.inner.poll_read(cx, &mut buf[..max]))?;
         *me.limit_ -= n as u64;
         Poll::Ready(Ok(n))
     }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        if self.limit_ == 0 {
+            return Poll::Ready(Ok(0));
+        }
+
+        let me = self.project();
+        let max = cmp::min(buf.remaining() as u64, *me.limit_) as usize;
+        let initialized = cmp::min(buf.initialized().len(), max);
+        let (filled, initialized) = {
+            let mut limited = BorrowedBuf::uninit(unsafe { &mut buf.unfilled_mut()[..max] });
+            unsafe {
+                limited.assume_init(initialized);
+            }
+            ready!(me.inner.poll_read_uninit(cx, limited.unfilled()))?;
+            (limited.filled().len(), limited.initialized().len())
+        };
+        unsafe {
+            buf.assume_init(initialized);
+        }
+        buf.advance(filled);
+        *me.limit_ -= filled as u64;
+        Poll::Ready(Ok(filled))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let limit = usize::try_from(self.limit_).unwrap_or(usize::MAX);
+        let (lower, upper) = self.inner.size_hint();
+        let lower = cmp::min(lower, limit);
+        let upper = match upper {
+            Some(upper) => cmp::min(upper, limit),
+            None => limit,
+        };
+        (lower, Some(upper))
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        if self.limit_ == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        self.project().inner.poll_read_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{block_on, AsyncRead, AsyncReadExt, BorrowedCursor};
+    use alloc::vec::Vec;
+    use core::{
+        cmp,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    /// Reads from `data` and records the largest cursor it was handed. `poll_read` panics, so
+    /// the test fails if the uninitialized path is not used.
+    struct Uninit<'a> {
+        data: &'a [u8],
+        largest: usize,
+    }
+
+    impl AsyncRead for Uninit<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            panic!("poll_read_uninit should be used");
+        }
+
+        fn poll_read_uninit(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            mut buf: BorrowedCursor<'_>,
+        ) -> Poll<Result<usize, Self::Error>> {
+            self.largest = cmp::max(self.largest, buf.remaining());
+            let n = cmp::min(buf.remaining(), self.data.len());
+            buf.put_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[test]
+    fn uninit_reads_are_limited() {
+        let mut reader = Uninit {
+            data: b"hello world",
+            largest: 0,
+        }
+        .take(5);
+        let mut buf = Vec::new();
+        assert_eq!(block_on(reader.read_to_end(&mut buf)).unwrap(), 5);
+        assert_eq!(buf, b"hello");
+        assert_eq!(reader.limit(), 0);
+        assert_eq!(reader.get_ref().largest, 5);
+        assert_eq!(reader.get_ref().data, b" world");
+    }
+
+    /// Reports a fixed readiness and reads `data` regardless of it.
+    struct Gate {
+        data: &'static [u8],
+        ready: Poll<Result<(), &'static str>>,
+    }
+
+    impl AsyncRead for Gate {
+        type Error = &'static str;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = buf.len().min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_read_ready(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::Error>> {
+            self.ready
+        }
+    }
+
+    fn read_ready<R: AsyncRead + Unpin>(reader: &mut R) -> Poll<Result<(), R::Error>> {
+        let mut cx = Context::from_waker(_futures::task::noop_waker_ref());
+        Pin::new(reader).poll_read_ready(&mut cx)
+    }
+
+    #[test]
+    fn readiness_is_forwarded_until_the_limit() {
+        let gate = |ready| Gate { data: b"", ready };
+        assert!(read_ready(&mut gate(Poll::Pending).take(1)).is_pending());
+        assert!(matches!(
+            read_ready(&mut gate(Poll::Ready(Ok(()))).take(1)),
+            Poll::Ready(Ok(()))
+        ));
+        assert!(matches!(
+            read_ready(&mut gate(Poll::Ready(Err("x"))).take(1)),
+            Poll::Ready(Err("x"))
+        ));
+        // At the limit the next read returns EOF at once, whatever the inner reader says.
+        assert!(matches!(
+            read_ready(&mut gate(Poll::Pending).take(0)),
+            Poll::Ready(Ok(()))
+        ));
+    }
 }
@@ -5,26 +5,27 @@ use core::{
     task::{Context, Poll},
 };
 
+/// Cancellation safe: closing can be restarted with a new future.
 #[derive(Debug)]
 pub struct Close<'a, A: ?Sized> {
-    a: &'a mut A,
+    a: Pin<&'a mut A>,
 }
 
-pub(super) fn close<A>(a: &mut A) -> Close<'_, A>
+pub(super) fn close<A>(a: Pin<&mut A>) -> Close<'_, A>
 where
-    A: AsyncWrite + Unpin + ?Sized,
+    A: AsyncWrite + ?Sized,
 {
     Close { a }
 }
 
 impl<A> Future for Close<'_, A>
 where
-    A: AsyncWrite + Unpin + ?Sized,
+    A: AsyncWrite + ?Sized,
 {
     type Output = Result<(), A::CloseError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         let me = &mut *self;
-        Pin::new(&mut *me.a).poll_close(cx)
+        me.a.as_mut().poll_close(cx)
     }
 }
@@ -5,26 +5,27 @@ use core::{
     task::{Context, Poll},
 };
 
+/// Cancellation safe: flushing can be restarted with a new future.
 #[derive(Debug)]
 pub struct Flush<'a, A: ?Sized> {
-    a: &'a mut A,
+    a: Pin<&'a mut A>,
 }
 
-pub(super) fn flush<A>(a: &mut A) -> Flush<'_, A>
+pub(super) fn flush<A>(a: Pin<&mut A>) -> Flush<'_, A>
 where
-    A: AsyncWrite + Unpin + ?Sized,
+    A: AsyncWrite + ?Sized,
 {
     Flush { a }
 }
 
 impl<A> Future for Flush<'_, A>
 where
-    A: AsyncWrite + Unpin + ?Sized,
+    A: AsyncWrite + ?Sized,
 {
     type Output = Result<(), A::FlushError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         let me = &mut *self;
-        Pin::new(&mut *me.a).poll_flush(cx)
+        me.a.as_mut().poll_flush(cx)
     }
 }
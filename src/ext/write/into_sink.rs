@@ -0,0 +1,301 @@
+use crate::{AsyncWrite, Classify, ErrorKind};
+use _futures::{ready, Sink};
+use bytes::Buf;
+use core::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use pin_project_lite::pin_project;
+
+pin_project! {
+    #[derive(Debug)]
+    #[must_use = "sinks do nothing unless polled"]
+    pub struct IntoSink<W, B> {
+        #[pin]
+        writer: W,
+        buffer: Option<B>,
+    }
+}
+
+pub(super) fn into_sink<W: AsyncWrite, B: Buf>(writer: W) -> IntoSink<W, B> {
+    IntoSink {
+        writer,
+        buffer: None,
+    }
+}
+
+impl<W, B> IntoSink<W, B> {
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+pub enum IntoSinkError<W: AsyncWrite + ?Sized> {
+    Write(W::WriteError),
+    Flush(W::FlushError),
+    Close(W::CloseError),
+    WriteZero,
+}
+
+impl<W> fmt::Debug for IntoSinkError<W>
+where
+    W: AsyncWrite + ?Sized,
+    W::WriteError: fmt::Debug,
+    W::FlushError: fmt::Debug,
+    W::CloseError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntoSinkError::Write(e) => f.debug_tuple("Write").field(e).finish(),
+            IntoSinkError::Flush(e) => f.debug_tuple("Flush").field(e).finish(),
+            IntoSinkError::Close(e) => f.debug_tuple("Close").field(e).finish(),
+            IntoSinkError::WriteZero => f.write_str("WriteZero"),
+        }
+    }
+}
+
+impl<W> fmt::Display for IntoSinkError<W>
+where
+    W: AsyncWrite + ?Sized,
+    W::WriteError: fmt::Display,
+    W::FlushError: fmt::Display,
+    W::CloseError: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntoSinkError::Write(e) => e.fmt(f),
+            IntoSinkError::Flush(e) => e.fmt(f),
+            IntoSinkError::Close(e) => e.fmt(f),
+            IntoSinkError::WriteZero => f.write_str("failed to write whole buffer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> std::error::Error for IntoSinkError<W>
+where
+    W: AsyncWrite + ?Sized,
+    W::WriteError: std::error::Error,
+    W::FlushError: std::error::Error,
+    W::CloseError: std::error::Error,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IntoSinkError::Write(e) => e.source(),
+            IntoSinkError::Flush(e) => e.source(),
+            IntoSinkError::Close(e) => e.source(),
+            IntoSinkError::WriteZero => None,
+        }
+    }
+}
+
+impl<W> Classify for IntoSinkError<W>
+where
+    W: AsyncWrite + ?Sized,
+    W::WriteError: Classify,
+    W::FlushError: Classify,
+    W::CloseError: Classify,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            IntoSinkError::Write(e) => e.kind(),
+            IntoSinkError::Flush(e) => e.kind(),
+            IntoSinkError::Close(e) => e.kind(),
+            IntoSinkError::WriteZero => ErrorKind::WriteZero,
+        }
+    }
+}
+
+impl<W: AsyncWrite, B: Buf> IntoSink<W, B> {
+    fn poll_flush_buffer(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), IntoSinkError<W>>> {
+        let mut this = self.project();
+        if let Some(buffer) = this.buffer.as_mut() {
+            while buffer.has_remaining() {
+                let n = ready!(this.writer.as_mut().poll_write(cx, buffer.bytes()))
+                    .map_err(IntoSinkError::Write)?;
+                if n == 0 {
+                    return Poll::Ready(Err(IntoSinkError::WriteZero));
+                }
+                buffer.advance(n);
+            }
+        }
+        *this.buffer = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite, B: Buf> Sink<B> for IntoSink<W, B> {
+    type Error = IntoSinkError<W>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush_buffer(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: B) -> Result<(), Self::Error> {
+        debug_assert!(self.buffer.is_none());
+        *self.project().buffer = Some(item);
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_flush_buffer(cx))?;
+        self.project()
+            .writer
+            .poll_flush(cx)
+            .map_err(IntoSinkError::Flush)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_flush_buffer(cx))?;
+        self.project()
+            .writer
+            .poll_close(cx)
+            .map_err(IntoSinkError::Close)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncWriteExt};
+    use alloc::vec::Vec;
+    use core::future::poll_fn;
+
+    /// Accepts at most two bytes per write, is pending before every write, and logs each call.
+    #[derive(Default)]
+    struct Slow {
+        data: Vec<u8>,
+        log: Vec<&'static str>,
+        ready: bool,
+    }
+
+    impl AsyncWrite for Slow {
+        type WriteError = void::Void;
+        type FlushError = void::Void;
+        type CloseError = void::Void;
+
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let n = buf.len().min(2);
+            self.data.extend_from_slice(&buf[..n]);
+            self.log.push("write");
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+        ) -> Poll<Result<(), Self::FlushError>> {
+            self.log.push("flush");
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+        ) -> Poll<Result<(), Self::CloseError>> {
+            self.log.push("close");
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn send(sink: &mut IntoSink<Slow, &'static [u8]>, item: &'static [u8]) {
+        block_on(poll_fn(|cx| Pin::new(&mut *sink).poll_ready(cx))).unwrap();
+        Pin::new(sink).start_send(item).unwrap();
+    }
+
+    #[test]
+    fn flush_writes_the_rest_of_the_item_first() {
+        let mut sink = Slow::default().into_sink();
+        send(&mut sink, b"hello");
+        assert!(sink.get_ref().data.is_empty());
+
+        block_on(poll_fn(|cx| Pin::new(&mut sink).poll_flush(cx))).unwrap();
+        assert_eq!(sink.get_ref().data, b"hello");
+        assert_eq!(sink.get_ref().log, ["write", "write", "write", "flush"]);
+    }
+
+    #[test]
+    fn ready_waits_for_the_previous_item() {
+        let mut sink = Slow::default().into_sink();
+        send(&mut sink, b"abc");
+        send(&mut sink, b"de");
+        assert_eq!(sink.get_ref().data, b"abc");
+        block_on(poll_fn(|cx| Pin::new(&mut sink).poll_close(cx))).unwrap();
+        assert_eq!(sink.get_ref().data, b"abcde");
+        assert_eq!(sink.get_ref().log, ["write", "write", "write", "close"]);
+    }
+
+    #[test]
+    fn close_writes_the_rest_of_the_item_first() {
+        let mut sink = Slow::default().into_sink();
+        send(&mut sink, b"abc");
+        block_on(poll_fn(|cx| Pin::new(&mut sink).poll_close(cx))).unwrap();
+        assert_eq!(sink.get_ref().data, b"abc");
+        assert_eq!(sink.get_ref().log, ["write", "write", "close"]);
+    }
+
+    #[test]
+    fn write_zero_is_an_error() {
+        struct Full;
+
+        impl AsyncWrite for Full {
+            type WriteError = void::Void;
+            type FlushError = void::Void;
+            type CloseError = void::Void;
+
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+                _: &[u8],
+            ) -> Poll<Result<usize, Self::WriteError>> {
+                Poll::Ready(Ok(0))
+            }
+
+            fn poll_flush(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+            ) -> Poll<Result<(), Self::FlushError>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+            ) -> Poll<Result<(), Self::CloseError>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let mut sink = Full.into_sink();
+        Pin::new(&mut sink).start_send(&b"x"[..]).unwrap();
+        assert!(matches!(
+            block_on(poll_fn(|cx| Pin::new(&mut sink).poll_flush(cx))),
+            Err(IntoSinkError::WriteZero)
+        ));
+    }
+}
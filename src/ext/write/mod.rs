@@ -1,6 +1,22 @@
-use crate::AsyncWrite;
+#[cfg(feature = "alloc")]
+use super::map_err::{box_err, BoxErr};
+#[cfg(feature = "std")]
+use super::map_err::{into_io, IntoIo};
+use super::map_err::{map_write_err, MapWriteErr};
+#[cfg(feature = "std")]
+use crate::Classify;
+#[cfg(feature = "alloc")]
+use crate::IntoBoxError;
+use crate::{poll::try_once, AsyncWrite};
+#[cfg(feature = "alloc")]
+use crate::{BoxedWriter, LocalBoxedWriter};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
 mod close;
 mod flush;
+mod into_sink;
+mod writable;
+#[allow(clippy::module_inception)]
 mod write;
 mod write_all;
 mod write_buf;
@@ -8,12 +24,17 @@ mod write_int;
 use bytes::Buf;
 use close::close;
 pub use close::Close;
+use core::pin::Pin;
 use flush::flush;
 pub use flush::Flush;
+use into_sink::into_sink;
+pub use into_sink::{IntoSink, IntoSinkError};
+use writable::writable;
+pub use writable::Writable;
 use write::write;
 pub use write::Write;
-use write_all::write_all;
-pub use write_all::WriteAll;
+use write_all::{write_all, write_all_resumable};
+pub use write_all::{WriteAll, WriteAllError, WriteAllResumable, WriteAllState};
 use write_buf::write_buf;
 pub use write_buf::WriteBuf;
 pub use write_int::{WriteI128, WriteI16, WriteI32, WriteI64, WriteI8};
@@ -24,11 +45,26 @@ macro_rules! write_impl {
         $(
             $(#[$outer:meta])*
             fn $name:ident(&mut self, n: $ty:ty) -> $($fut:ident)*;
+            fn $pinned:ident(self: Pin<&mut Self>);
         )*
     ) => {
         $(
+            /// Writes `n` in big-endian byte order.
+            ///
+            /// **Breaking:** resolves to [`WriteAllError`] rather than a bare `Self::WriteError`,
+            /// and fails with `WriteAllError::WriteZero` if the writer stops accepting bytes
+            /// before the whole value is written, instead of retrying forever.
             $(#[$outer])*
-            fn $name<'a>(&'a mut self, n: $ty) -> $($fut)*<&'a mut Self> where Self: Unpin {
+            fn $name(&mut self, n: $ty) -> $($fut)*<&mut Self> where Self: Unpin {
+                $($fut)*::new(self, n)
+            }
+
+            #[doc = concat!(
+                "Pinned variant of [`", stringify!($name), "`](Self::", stringify!($name),
+                "), with the same [`WriteAllError`] behaviour."
+            )]
+            $(#[$outer])*
+            fn $pinned(self: Pin<&mut Self>, n: $ty) -> $($fut)*<Pin<&mut Self>> {
                 $($fut)*::new(self, n)
             }
         )*
@@ -40,12 +76,49 @@ pub trait AsyncWriteExt: AsyncWrite {
     where
         Self: Unpin,
     {
+        write(Pin::new(self), src)
+    }
+
+    fn write_pinned<'a>(self: Pin<&'a mut Self>, src: &'a [u8]) -> Write<'a, Self> {
         write(self, src)
     }
 
+    fn writable(&mut self) -> Writable<'_, Self>
+    where
+        Self: Unpin,
+    {
+        writable(Pin::new(self))
+    }
+
+    fn writable_pinned(self: Pin<&mut Self>) -> Writable<'_, Self> {
+        writable(self)
+    }
+
+    /// Polls a single write with a no-op waker, returning `Ok(None)` if the writer is not ready.
+    fn try_write(&mut self, src: &[u8]) -> Result<Option<usize>, Self::WriteError>
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).try_write_pinned(src)
+    }
+
+    fn try_write_pinned(
+        self: Pin<&mut Self>,
+        src: &[u8],
+    ) -> Result<Option<usize>, Self::WriteError> {
+        try_once(|cx| self.poll_write(cx, src))
+    }
+
     fn write_buf<'a, B>(&'a mut self, src: &'a mut B) -> WriteBuf<'a, Self, B>
     where
-        Self: Sized,
+        Self: Unpin,
+        B: Buf,
+    {
+        write_buf(Pin::new(self), src)
+    }
+
+    fn write_buf_pinned<'a, B>(self: Pin<&'a mut Self>, src: &'a mut B) -> WriteBuf<'a, Self, B>
+    where
         B: Buf,
     {
         write_buf(self, src)
@@ -55,26 +128,63 @@ pub trait AsyncWriteExt: AsyncWrite {
     where
         Self: Unpin,
     {
+        write_all(Pin::new(self), src)
+    }
+
+    fn write_all_pinned<'a>(self: Pin<&'a mut Self>, src: &'a [u8]) -> WriteAll<'a, Self> {
         write_all(self, src)
     }
 
+    fn write_all_resumable<'a>(
+        &'a mut self,
+        src: &'a [u8],
+        state: &'a mut WriteAllState,
+    ) -> WriteAllResumable<'a, Self>
+    where
+        Self: Unpin,
+    {
+        write_all_resumable(Pin::new(self), src, state)
+    }
+
+    fn write_all_resumable_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        src: &'a [u8],
+        state: &'a mut WriteAllState,
+    ) -> WriteAllResumable<'a, Self> {
+        write_all_resumable(self, src, state)
+    }
+
     write_impl! {
         fn write_u8(&mut self, n: u8) -> WriteU8;
+        fn write_u8_pinned(self: Pin<&mut Self>);
         fn write_i8(&mut self, n: i8) -> WriteI8;
+        fn write_i8_pinned(self: Pin<&mut Self>);
         fn write_u16(&mut self, n: u16) -> WriteU16;
+        fn write_u16_pinned(self: Pin<&mut Self>);
         fn write_i16(&mut self, n: i16) -> WriteI16;
+        fn write_i16_pinned(self: Pin<&mut Self>);
         fn write_u32(&mut self, n: u32) -> WriteU32;
+        fn write_u32_pinned(self: Pin<&mut Self>);
         fn write_i32(&mut self, n: i32) -> WriteI32;
+        fn write_i32_pinned(self: Pin<&mut Self>);
         fn write_u64(&mut self, n: u64) -> WriteU64;
+        fn write_u64_pinned(self: Pin<&mut Self>);
         fn write_i64(&mut self, n: i64) -> WriteI64;
+        fn write_i64_pinned(self: Pin<&mut Self>);
         fn write_u128(&mut self, n: u128) -> WriteU128;
+        fn write_u128_pinned(self: Pin<&mut Self>);
         fn write_i128(&mut self, n: i128) -> WriteI128;
+        fn write_i128_pinned(self: Pin<&mut Self>);
     }
 
     fn flush(&mut self) -> Flush<'_, Self>
     where
         Self: Unpin,
     {
+        flush(Pin::new(self))
+    }
+
+    fn flush_pinned(self: Pin<&mut Self>) -> Flush<'_, Self> {
         flush(self)
     }
 
@@ -82,8 +192,212 @@ pub trait AsyncWriteExt: AsyncWrite {
     where
         Self: Unpin,
     {
+        close(Pin::new(self))
+    }
+
+    fn close_pinned(self: Pin<&mut Self>) -> Close<'_, Self> {
         close(self)
     }
+
+    fn into_sink<B: Buf>(self) -> IntoSink<Self, B>
+    where
+        Self: Sized,
+    {
+        into_sink(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed_writer<'a>(
+        self,
+    ) -> BoxedWriter<'a, Self::WriteError, Self::FlushError, Self::CloseError>
+    where
+        Self: Sized + Send + 'a,
+    {
+        Box::pin(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed_local_writer<'a>(
+        self,
+    ) -> LocalBoxedWriter<'a, Self::WriteError, Self::FlushError, Self::CloseError>
+    where
+        Self: Sized + 'a,
+    {
+        Box::pin(self)
+    }
+
+    fn map_write_err<F, G, H, WE, FE, CE>(
+        self,
+        write: F,
+        flush: G,
+        close: H,
+    ) -> MapWriteErr<Self, F, G, H>
+    where
+        Self: Sized,
+        F: FnMut(Self::WriteError) -> WE,
+        G: FnMut(Self::FlushError) -> FE,
+        H: FnMut(Self::CloseError) -> CE,
+    {
+        map_write_err(self, write, flush, close)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn box_write_err(self) -> BoxErr<Self>
+    where
+        Self: Sized,
+        Self::WriteError: IntoBoxError,
+        Self::FlushError: IntoBoxError,
+        Self::CloseError: IntoBoxError,
+    {
+        box_err(self)
+    }
+
+    #[cfg(feature = "std")]
+    fn into_io_write(self) -> IntoIo<Self>
+    where
+        Self: Sized,
+        Self::WriteError: Classify + Into<Box<dyn std::error::Error + Send + Sync>>,
+        Self::FlushError: Classify + Into<Box<dyn std::error::Error + Send + Sync>>,
+        Self::CloseError: Classify + Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        into_io(self)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn into_tokio_write(self) -> crate::TokioCompat<Self>
+    where
+        Self: Sized,
+    {
+        crate::TokioCompat::new(self)
+    }
+
+    #[cfg(feature = "tokio1")]
+    fn into_tokio1_write(self) -> crate::Tokio1Compat<Self>
+    where
+        Self: Sized,
+    {
+        crate::Tokio1Compat::new(self)
+    }
+
+    #[cfg(feature = "futures")]
+    fn into_futures_write(self) -> crate::FuturesCompat<Self>
+    where
+        Self: Sized,
+    {
+        crate::FuturesCompat::new(self)
+    }
 }
 
 impl<W: AsyncWrite + ?Sized> AsyncWriteExt for W {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_on;
+    use core::{
+        marker::PhantomPinned,
+        pin::pin,
+        task::{Context, Poll},
+    };
+    use pin_project_lite::pin_project;
+    use void::Void;
+
+    pin_project! {
+        /// A `!Unpin` writer into a fixed buffer, so it can only be used pinned.
+        struct Pinned {
+            buf: [u8; 16],
+            len: usize,
+            closed: bool,
+            #[pin]
+            _pin: PhantomPinned,
+        }
+    }
+
+    impl AsyncWrite for Pinned {
+        type WriteError = Void;
+        type FlushError = Void;
+        type CloseError = Void;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            src: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            let me = self.project();
+            let n = src.len().min(me.buf.len() - *me.len);
+            me.buf[*me.len..*me.len + n].copy_from_slice(&src[..n]);
+            *me.len += n;
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            *self.project().closed = true;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn pinned_futures_over_a_trait_object() {
+        let mut writer = pin!(Pinned {
+            buf: [0; 16],
+            len: 0,
+            closed: false,
+            _pin: PhantomPinned,
+        });
+        {
+            let mut writer: Pin<
+                &mut dyn AsyncWrite<WriteError = Void, FlushError = Void, CloseError = Void>,
+            > = writer.as_mut();
+            block_on(writer.as_mut().write_u16_pinned(0x0102)).unwrap();
+            block_on(writer.as_mut().write_all_pinned(b"hello")).unwrap();
+            assert_eq!(block_on(writer.as_mut().write_pinned(b", ")).unwrap(), 2);
+            assert_eq!(writer.as_mut().try_write_pinned(b"world").unwrap(), Some(5));
+            block_on(writer.as_mut().flush_pinned()).unwrap();
+            block_on(writer.as_mut().close_pinned()).unwrap();
+        }
+        assert_eq!(&writer.buf[..writer.len], b"\x01\x02hello, world");
+        assert!(writer.closed);
+    }
+
+    /// Accepts writes only on every other poll.
+    struct Alternating(bool);
+
+    impl AsyncWrite for Alternating {
+        type WriteError = Void;
+        type FlushError = Void;
+        type CloseError = Void;
+
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            src: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            self.0 = !self.0;
+            if self.0 {
+                Poll::Ready(Ok(src.len()))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn try_write_is_none_while_pending() {
+        let mut writer = Alternating(true);
+        assert_eq!(writer.try_write(b"abc").unwrap(), None);
+        assert_eq!(writer.try_write(b"abc").unwrap(), Some(3));
+        assert_eq!(writer.try_write(b"abc").unwrap(), None);
+    }
+}
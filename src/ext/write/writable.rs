@@ -0,0 +1,32 @@
+use crate::AsyncWrite;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Cancellation safe: no data is written while waiting.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Writable<'a, W: ?Sized> {
+    writer: Pin<&'a mut W>,
+}
+
+pub(super) fn writable<W>(writer: Pin<&mut W>) -> Writable<'_, W>
+where
+    W: AsyncWrite + ?Sized,
+{
+    Writable { writer }
+}
+
+impl<W> Future for Writable<'_, W>
+where
+    W: AsyncWrite + ?Sized,
+{
+    type Output = Result<(), W::WriteError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        me.writer.as_mut().poll_write_ready(cx)
+    }
+}
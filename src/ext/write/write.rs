@@ -6,28 +6,29 @@ use core::{
     task::{Context, Poll},
 };
 
+/// Cancellation safe: if dropped before completing, no data was written.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Write<'a, W: ?Sized> {
-    writer: &'a mut W,
+    writer: Pin<&'a mut W>,
     buf: &'a [u8],
 }
 
-pub(crate) fn write<'a, W>(writer: &'a mut W, buf: &'a [u8]) -> Write<'a, W>
+pub(crate) fn write<'a, W>(writer: Pin<&'a mut W>, buf: &'a [u8]) -> Write<'a, W>
 where
-    W: AsyncWrite + Unpin + ?Sized,
+    W: AsyncWrite + ?Sized,
 {
     Write { writer, buf }
 }
 
 impl<W> Future for Write<'_, W>
 where
-    W: AsyncWrite + Unpin + ?Sized,
+    W: AsyncWrite + ?Sized,
 {
     type Output = Result<usize, W::WriteError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<usize, W::WriteError>> {
         let me = &mut *self;
-        Pin::new(&mut *me.writer).poll_write(cx, me.buf)
+        me.writer.as_mut().poll_write(cx, me.buf)
     }
 }
@@ -1,29 +1,82 @@
-use crate::AsyncWrite;
+use crate::{poll::poll_write_all, AsyncWrite, Classify, ErrorKind};
 use _futures::ready;
 use core::{
+    fmt,
     future::Future,
-    mem,
     pin::Pin,
     task::{Context, Poll},
 };
 
+/// Not cancellation safe: if dropped before completing, there is no way to tell how much of
+/// `buf` was written. Use [`write_all_resumable`](crate::AsyncWriteExt::write_all_resumable)
+/// when the future may be dropped, for example in a `select!`.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct WriteAll<'a, W: ?Sized> {
-    writer: &'a mut W,
+    writer: Pin<&'a mut W>,
     buf: &'a [u8],
+    written: usize,
 }
 
-pub(crate) fn write_all<'a, W>(writer: &'a mut W, buf: &'a [u8]) -> WriteAll<'a, W>
+pub(crate) fn write_all<'a, W>(writer: Pin<&'a mut W>, buf: &'a [u8]) -> WriteAll<'a, W>
 where
-    W: AsyncWrite + Unpin + ?Sized,
+    W: AsyncWrite + ?Sized,
 {
-    WriteAll { writer, buf }
+    WriteAll {
+        writer,
+        buf,
+        written: 0,
+    }
+}
+
+/// Progress of a [`WriteAllResumable`], kept by the caller so that a dropped future can be
+/// continued by a new one.
+///
+/// The state is reset when the write completes successfully. After an error it keeps the
+/// progress made so far; call [`reset`](WriteAllState::reset) to start over.
+#[derive(Debug, Default, Clone)]
+pub struct WriteAllState {
+    pos: usize,
+}
+
+impl WriteAllState {
+    pub fn new() -> Self {
+        WriteAllState { pos: 0 }
+    }
+
+    /// Number of bytes of the buffer already written.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+pub(crate) fn write_all_resumable<'a, W>(
+    writer: Pin<&'a mut W>,
+    buf: &'a [u8],
+    state: &'a mut WriteAllState,
+) -> WriteAllResumable<'a, W>
+where
+    W: AsyncWrite + ?Sized,
+{
+    WriteAllResumable { writer, buf, state }
+}
+
+/// Cancellation safe, provided the next future is given the same buffer and state.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteAllResumable<'a, W: ?Sized> {
+    writer: Pin<&'a mut W>,
+    buf: &'a [u8],
+    state: &'a mut WriteAllState,
 }
 
 #[derive(Debug)]
 pub enum WriteAllError<T> {
-    WriteZero,
+    WriteZero(usize),
     Write(T),
 }
 
@@ -33,9 +86,39 @@ impl<T> From<T> for WriteAllError<T> {
     }
 }
 
+impl<T: fmt::Display> fmt::Display for WriteAllError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteAllError::WriteZero(written) => {
+                write!(f, "failed to write whole buffer, wrote {} bytes", written)
+            }
+            WriteAllError::Write(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::error::Error> std::error::Error for WriteAllError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteAllError::WriteZero(_) => None,
+            WriteAllError::Write(e) => e.source(),
+        }
+    }
+}
+
+impl<T: Classify> Classify for WriteAllError<T> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            WriteAllError::WriteZero(_) => ErrorKind::WriteZero,
+            WriteAllError::Write(e) => e.kind(),
+        }
+    }
+}
+
 impl<W> Future for WriteAll<'_, W>
 where
-    W: AsyncWrite + Unpin,
+    W: AsyncWrite + ?Sized,
 {
     type Output = Result<(), WriteAllError<W::WriteError>>;
 
@@ -44,17 +127,28 @@ where
         cx: &mut Context,
     ) -> Poll<Result<(), WriteAllError<W::WriteError>>> {
         let me = &mut *self;
-        while !me.buf.is_empty() {
-            let n = ready!(Pin::new(&mut me.writer).poll_write(cx, me.buf))?;
-            {
-                let (_, rest) = mem::replace(&mut me.buf, &[]).split_at(n);
-                me.buf = rest;
-            }
-            if n == 0 {
-                return Poll::Ready(Err(WriteAllError::WriteZero));
-            }
-        }
+        poll_write_all(me.writer.as_mut(), cx, me.buf, &mut me.written)
+    }
+}
+
+impl<W> Future for WriteAllResumable<'_, W>
+where
+    W: AsyncWrite + ?Sized,
+{
+    type Output = Result<(), WriteAllError<W::WriteError>>;
 
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), WriteAllError<W::WriteError>>> {
+        let me = &mut *self;
+        ready!(poll_write_all(
+            me.writer.as_mut(),
+            cx,
+            me.buf,
+            &mut me.state.pos
+        ))?;
+        me.state.reset();
         Poll::Ready(Ok(()))
     }
 }
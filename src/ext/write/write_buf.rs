@@ -7,16 +7,17 @@ use core::{
     task::{Context, Poll},
 };
 
+/// Cancellation safe: if dropped before completing, no data was written.
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
-pub struct WriteBuf<'a, W, B> {
-    writer: &'a mut W,
+pub struct WriteBuf<'a, W: ?Sized, B> {
+    writer: Pin<&'a mut W>,
     buf: &'a mut B,
 }
 
-pub(crate) fn write_buf<'a, W, B>(writer: &'a mut W, buf: &'a mut B) -> WriteBuf<'a, W, B>
+pub(crate) fn write_buf<'a, W, B>(writer: Pin<&'a mut W>, buf: &'a mut B) -> WriteBuf<'a, W, B>
 where
-    W: AsyncWrite,
+    W: AsyncWrite + ?Sized,
     B: Buf,
 {
     WriteBuf { writer, buf }
@@ -24,15 +25,13 @@ where
 
 impl<W, B> Future for WriteBuf<'_, W, B>
 where
-    W: AsyncWrite,
+    W: AsyncWrite + ?Sized,
     B: Buf,
 {
     type Output = Result<usize, W::WriteError>;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<usize, W::WriteError>> {
-        unsafe {
-            let me = self.get_unchecked_mut();
-            Pin::new_unchecked(&mut *me.writer).poll_write_buf(cx, &mut me.buf)
-        }
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<usize, W::WriteError>> {
+        let me = &mut *self;
+        Pin::new(&mut me.writer).poll_write_buf(cx, me.buf)
     }
 }
@@ -1,4 +1,4 @@
-use crate::AsyncWrite;
+use crate::{poll, AsyncWrite, WriteAllError};
 use bytes::BufMut;
 use core::{
     future::Future,
@@ -10,23 +10,26 @@ use pin_project_lite::pin_project;
 
 macro_rules! writer {
     ($name:ident, $ty:ty, $writer:ident) => {
-        writer!($name, $ty, $writer, size_of::<$ty>());
-    };
-    ($name:ident, $ty:ty, $writer:ident, $bytes:expr) => {
         pin_project! {
+            /// Fails with `WriteAllError::WriteZero` if the writer stops accepting bytes before
+            /// the whole value is written.
+            ///
+            /// Not cancellation safe: if dropped before completing, there is no way to tell how
+            /// many bytes were written. Encode the value and use `write_all_resumable` instead
+            /// when the future may be dropped.
             #[doc(hidden)]
             pub struct $name<W> {
                 #[pin]
                 dst: W,
-                buf: [u8; $bytes],
-                written: u8,
+                buf: [u8; size_of::<$ty>()],
+                written: usize,
             }
         }
 
         impl<W> $name<W> {
             pub(crate) fn new(w: W, value: $ty) -> Self {
                 let mut writer = $name {
-                    buf: [0; $bytes],
+                    buf: [0; size_of::<$ty>()],
                     written: 0,
                     dst: w,
                 };
@@ -39,74 +42,18 @@ macro_rules! writer {
         where
             W: AsyncWrite,
         {
-            type Output = Result<(), W::WriteError>;
-
-            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-                let mut me = self.project();
-
-                if *me.written == $bytes as u8 {
-                    return Poll::Ready(Ok(()));
-                }
-
-                while *me.written < $bytes as u8 {
-                    *me.written += match me
-                        .dst
-                        .as_mut()
-                        .poll_write(cx, &me.buf[*me.written as usize..])
-                    {
-                        Poll::Pending => return Poll::Pending,
-                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
-                        Poll::Ready(Ok(n)) => n as u8,
-                    };
-                }
-                Poll::Ready(Ok(()))
-            }
-        }
-    };
-}
-
-macro_rules! writer8 {
-    ($name:ident, $ty:ty) => {
-        pin_project! {
-            #[doc(hidden)]
-            pub struct $name<W> {
-                #[pin]
-                dst: W,
-                byte: $ty,
-            }
-        }
-
-        impl<W> $name<W> {
-            pub(crate) fn new(dst: W, byte: $ty) -> Self {
-                Self { dst, byte }
-            }
-        }
-
-        impl<W> Future for $name<W>
-        where
-            W: AsyncWrite,
-        {
-            type Output = Result<(), W::WriteError>;
+            type Output = Result<(), WriteAllError<W::WriteError>>;
 
             fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
                 let me = self.project();
-
-                let buf = [*me.byte as u8];
-
-                match me.dst.poll_write(cx, &buf[..]) {
-                    Poll::Pending => Poll::Pending,
-                    Poll::Ready(Err(e)) => Poll::Ready(Err(e.into())),
-                    Poll::Ready(Ok(0)) => Poll::Pending,
-                    Poll::Ready(Ok(1)) => Poll::Ready(Ok(())),
-                    Poll::Ready(Ok(_)) => unreachable!(),
-                }
+                poll::poll_write_all(me.dst, cx, &me.buf[..], me.written)
             }
         }
     };
 }
 
-writer8!(WriteU8, u8);
-writer8!(WriteI8, i8);
+writer!(WriteU8, u8, put_u8);
+writer!(WriteI8, i8, put_i8);
 
 writer!(WriteU16, u16, put_u16);
 writer!(WriteU32, u32, put_u32);
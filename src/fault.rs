@@ -0,0 +1,493 @@
+use crate::{AsyncRead, AsyncWrite};
+use _futures::ready;
+use core::{
+    cmp, fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use pin_project_lite::pin_project;
+
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Faults {
+    rng: Option<Rng>,
+    max_chunk: Option<usize>,
+    pending: bool,
+    yielded: bool,
+    transferred: u64,
+    error_at: Option<u64>,
+}
+
+impl Faults {
+    fn new(rng: Option<Rng>) -> Self {
+        Faults {
+            rng,
+            max_chunk: None,
+            pending: false,
+            yielded: false,
+            transferred: 0,
+            error_at: None,
+        }
+    }
+
+    fn should_yield(&mut self, cx: &mut Context) -> bool {
+        if !self.pending {
+            return false;
+        }
+        let yield_now = match &mut self.rng {
+            Some(rng) => !self.yielded && rng.next() & 1 == 0,
+            None => !self.yielded,
+        };
+        self.yielded = yield_now;
+        if yield_now {
+            cx.waker().wake_by_ref();
+        }
+        yield_now
+    }
+
+    fn error_due(&self) -> bool {
+        self.error_at.is_some_and(|at| self.transferred >= at)
+    }
+
+    fn chunk(&mut self, len: usize) -> usize {
+        let mut n = len;
+        if let Some(max) = self.max_chunk {
+            let max = match &mut self.rng {
+                Some(rng) => (rng.next() % max as u64) as usize + 1,
+                None => max,
+            };
+            n = cmp::min(n, max);
+        }
+        if let Some(at) = self.error_at {
+            n = cmp::min(n as u64, at.saturating_sub(self.transferred)) as usize;
+        }
+        n
+    }
+}
+
+pin_project! {
+    pub struct FaultyReader<R: AsyncRead> {
+        #[pin]
+        inner: R,
+        faults: Faults,
+        error: Option<R::Error>,
+    }
+}
+
+impl<R: AsyncRead> FaultyReader<R> {
+    pub fn new(inner: R) -> Self {
+        FaultyReader {
+            inner,
+            faults: Faults::new(None),
+            error: None,
+        }
+    }
+
+    pub fn seeded(inner: R, seed: u64) -> Self {
+        FaultyReader {
+            inner,
+            faults: Faults::new(Some(Rng::new(seed))),
+            error: None,
+        }
+    }
+
+    pub fn max_chunk(mut self, max: usize) -> Self {
+        assert!(max > 0, "max_chunk must be at least 1");
+        self.faults.max_chunk = Some(max);
+        self
+    }
+
+    pub fn pending(mut self, pending: bool) -> Self {
+        self.faults.pending = pending;
+        self
+    }
+
+    pub fn error_after(mut self, bytes: u64, error: R::Error) -> Self {
+        self.faults.error_at = Some(self.faults.transferred + bytes);
+        self.error = Some(error);
+        self
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.faults.transferred
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + fmt::Debug> fmt::Debug for FaultyReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultyReader")
+            .field("inner", &self.inner)
+            .field("faults", &self.faults)
+            .finish()
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for FaultyReader<R> {
+    type Error = R::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.project();
+
+        if me.faults.should_yield(cx) {
+            return Poll::Pending;
+        }
+        if me.faults.error_due() {
+            if let Some(e) = me.error.take() {
+                me.faults.error_at = None;
+                return Poll::Ready(Err(e));
+            }
+        }
+
+        let max = me.faults.chunk(buf.len());
+        let n = ready!(me.inner.poll_read(cx, &mut buf[..max]))?;
+        me.faults.transferred += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.project().inner.poll_read_ready(cx)
+    }
+}
+
+pin_project! {
+    pub struct FaultyWriter<W: AsyncWrite> {
+        #[pin]
+        inner: W,
+        faults: Faults,
+        error: Option<W::WriteError>,
+    }
+}
+
+impl<W: AsyncWrite> FaultyWriter<W> {
+    pub fn new(inner: W) -> Self {
+        FaultyWriter {
+            inner,
+            faults: Faults::new(None),
+            error: None,
+        }
+    }
+
+    pub fn seeded(inner: W, seed: u64) -> Self {
+        FaultyWriter {
+            inner,
+            faults: Faults::new(Some(Rng::new(seed))),
+            error: None,
+        }
+    }
+
+    pub fn max_chunk(mut self, max: usize) -> Self {
+        assert!(max > 0, "max_chunk must be at least 1");
+        self.faults.max_chunk = Some(max);
+        self
+    }
+
+    pub fn pending(mut self, pending: bool) -> Self {
+        self.faults.pending = pending;
+        self
+    }
+
+    pub fn error_after(mut self, bytes: u64, error: W::WriteError) -> Self {
+        self.faults.error_at = Some(self.faults.transferred + bytes);
+        self.error = Some(error);
+        self
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.faults.transferred
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite + fmt::Debug> fmt::Debug for FaultyWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultyWriter")
+            .field("inner", &self.inner)
+            .field("faults", &self.faults)
+            .finish()
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for FaultyWriter<W> {
+    type WriteError = W::WriteError;
+    type FlushError = W::FlushError;
+    type CloseError = W::CloseError;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        let me = self.project();
+
+        if me.faults.should_yield(cx) {
+            return Poll::Pending;
+        }
+        if me.faults.error_due() {
+            if let Some(e) = me.error.take() {
+                me.faults.error_at = None;
+                return Poll::Ready(Err(e));
+            }
+        }
+
+        let max = me.faults.chunk(buf.len());
+        let n = ready!(me.inner.poll_write(cx, &buf[..max]))?;
+        me.faults.transferred += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        self.project().inner.poll_close(cx)
+    }
+
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        self.project().inner.poll_write_ready(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncWriteExt};
+    use _futures::task::{waker, ArcWake};
+    use alloc::{sync::Arc, vec::Vec};
+    use core::{
+        future::poll_fn,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    struct Source(&'static [u8]);
+
+    impl AsyncRead for Source {
+        type Error = &'static str;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = cmp::min(buf.len(), self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    /// Records the size of every write it accepts.
+    #[derive(Default)]
+    struct Sink {
+        data: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl AsyncWrite for Sink {
+        type WriteError = &'static str;
+        type FlushError = &'static str;
+        type CloseError = &'static str;
+
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            self.data.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    struct Count(AtomicUsize);
+
+    impl ArcWake for Count {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn read_once<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> Result<usize, R::Error> {
+        block_on(poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, buf)))
+    }
+
+    /// Polls once per step, recording `None` for `Pending` and the byte count otherwise.
+    fn trace<R: AsyncRead<Error = &'static str> + Unpin>(mut reader: R) -> Vec<Option<usize>> {
+        let count = Arc::new(Count(AtomicUsize::new(0)));
+        let waker = waker(count);
+        let mut cx = Context::from_waker(&waker);
+        let mut buf = [0; 16];
+        let mut trace = Vec::new();
+        loop {
+            match Pin::new(&mut reader).poll_read(&mut cx, &mut buf) {
+                Poll::Pending => trace.push(None),
+                Poll::Ready(Ok(0)) => return trace,
+                Poll::Ready(Ok(n)) => trace.push(Some(n)),
+                Poll::Ready(Err(e)) => panic!("unexpected error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn max_chunk_splits_reads() {
+        let mut reader = FaultyReader::new(Source(b"abcdefgh")).max_chunk(3);
+        let mut buf = [0; 16];
+        let mut sizes = Vec::new();
+        loop {
+            match read_once(&mut reader, &mut buf).unwrap() {
+                0 => break,
+                n => sizes.push(n),
+            }
+        }
+        assert_eq!(sizes, [3, 3, 2]);
+        assert_eq!(reader.bytes_read(), 8);
+    }
+
+    #[test]
+    fn max_chunk_splits_writes() {
+        let mut writer = FaultyWriter::new(Sink::default()).max_chunk(3);
+        block_on(writer.write_all(b"abcdefgh")).unwrap();
+        assert_eq!(writer.bytes_written(), 8);
+        let sink = writer.into_inner();
+        assert_eq!(sink.writes, [3, 3, 2]);
+        assert_eq!(sink.data, b"abcdefgh");
+    }
+
+    #[test]
+    fn injected_pending_wakes_the_task() {
+        let count = Arc::new(Count(AtomicUsize::new(0)));
+        let waker = waker(count.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut reader = FaultyReader::new(Source(b"ab")).pending(true);
+        let mut buf = [0; 4];
+
+        assert!(Pin::new(&mut reader)
+            .poll_read(&mut cx, &mut buf)
+            .is_pending());
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+        match Pin::new(&mut reader).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(2)) => {}
+            other => panic!("expected Ready(Ok(2)), got {:?}", other),
+        }
+        assert!(Pin::new(&mut reader)
+            .poll_read(&mut cx, &mut buf)
+            .is_pending());
+        assert_eq!(count.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn read_error_lands_on_the_configured_byte() {
+        let mut reader = FaultyReader::new(Source(b"abcdefghij"))
+            .max_chunk(4)
+            .error_after(6, "boom");
+        let mut buf = [0; 16];
+        assert_eq!(read_once(&mut reader, &mut buf), Ok(4));
+        assert_eq!(read_once(&mut reader, &mut buf), Ok(2));
+        assert_eq!(reader.bytes_read(), 6);
+        assert_eq!(read_once(&mut reader, &mut buf), Err("boom"));
+        assert_eq!(read_once(&mut reader, &mut buf), Ok(4));
+        assert_eq!(&buf[..4], b"ghij");
+    }
+
+    #[test]
+    fn write_error_lands_on_the_configured_byte() {
+        let mut writer = FaultyWriter::new(Sink::default()).error_after(5, "boom");
+        assert!(block_on(writer.write_all(b"abcdefgh")).is_err());
+        assert_eq!(writer.bytes_written(), 5);
+        assert_eq!(writer.get_ref().data, b"abcde");
+    }
+
+    #[test]
+    fn error_after_zero_fails_first_read() {
+        let mut reader = FaultyReader::new(Source(b"ab")).error_after(0, "boom");
+        let mut buf = [0; 4];
+        assert_eq!(read_once(&mut reader, &mut buf), Err("boom"));
+        assert_eq!(read_once(&mut reader, &mut buf), Ok(2));
+    }
+
+    #[test]
+    fn seed_determines_the_schedule() {
+        const DATA: &[u8] = b"the quick brown fox jumps over the lazy dog";
+        let faulty = |seed| {
+            FaultyReader::seeded(Source(DATA), seed)
+                .max_chunk(5)
+                .pending(true)
+        };
+        let first = trace(faulty(7));
+        assert_eq!(first, trace(faulty(7)));
+        assert_ne!(first, trace(faulty(8)));
+        let total: usize = first.iter().flatten().sum();
+        assert_eq!(total, DATA.len());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{block_on, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use ::genio::{Read as GRead, Write as GWrite};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[derive(Debug)]
+pub struct Compat<T>(T);
+
+impl<T> Compat<T> {
+    pub fn new(input: T) -> Self {
+        Compat(input)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Unpin + GRead> AsyncRead for Compat<T> {
+    type Error = T::ReadError;
+
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        Poll::Ready(GRead::read(&mut self.0, buf))
+    }
+}
+
+impl<T: Unpin + GWrite> AsyncWrite for Compat<T> {
+    type WriteError = T::WriteError;
+    type FlushError = T::FlushError;
+    type CloseError = T::FlushError;
+
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        Poll::Ready(GWrite::write(&mut self.0, buf))
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context,
+    ) -> Poll<Result<(), Self::FlushError>> {
+        Poll::Ready(GWrite::flush(&mut self.0))
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context,
+    ) -> Poll<Result<(), Self::CloseError>> {
+        Poll::Ready(GWrite::flush(&mut self.0))
+    }
+}
+
+#[derive(Debug)]
+pub struct Blocking<T>(T);
+
+impl<T> Blocking<T> {
+    pub fn new(input: T) -> Self {
+        Blocking(input)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Unpin + AsyncWrite> Blocking<T> {
+    pub fn close(&mut self) -> Result<(), T::CloseError> {
+        block_on(self.0.close())
+    }
+}
+
+impl<T: Unpin + AsyncRead> GRead for Blocking<T> {
+    type ReadError = T::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::ReadError> {
+        block_on(AsyncReadExt::read(&mut self.0, buf))
+    }
+
+    fn available_bytes(&self, at_least: usize) -> bool {
+        match self.0.size_hint() {
+            (_, Some(upper)) => upper >= at_least,
+            _ => true,
+        }
+    }
+}
+
+impl<T: Unpin + AsyncWrite> GWrite for Blocking<T> {
+    type WriteError = T::WriteError;
+    type FlushError = T::FlushError;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::WriteError> {
+        block_on(AsyncWriteExt::write(&mut self.0, buf))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::FlushError> {
+        block_on(AsyncWriteExt::flush(&mut self.0))
+    }
+
+    fn size_hint(&mut self, _bytes: usize) {}
+}
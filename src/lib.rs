@@ -3,30 +3,102 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
-use _futures::ready;
 use core::{
+    fmt,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
 };
 use void::Void;
 
+mod buf;
+pub use buf::{BorrowedBuf, BorrowedCursor};
 mod read;
 pub use read::AsyncRead;
+mod buf_read;
+pub use buf_read::{AsyncBufRead, Reader};
 mod write;
 pub use write::AsyncWrite;
+mod boxed;
+pub use boxed::AsyncDuplex;
+#[cfg(feature = "alloc")]
+pub use boxed::{
+    BoxedDuplex, BoxedReader, BoxedWriter, LocalBoxedDuplex, LocalBoxedReader, LocalBoxedWriter,
+};
 mod ext;
 pub use ext::*;
+mod checked;
+pub use checked::{Checked, CheckedError, ContractViolation};
+mod either;
+pub use _futures::future::Either;
+pub use either::EitherError;
+mod error;
+pub use error::{Classify, ErrorKind};
+#[cfg(feature = "alloc")]
+pub use error::{BoxError, IntoBoxError};
+mod owned;
+pub use owned::{AsyncReadOwned, AsyncWriteOwned, IoBuf, IoBufMut, Owned, ReadOwned, WriteOwned};
+#[cfg(feature = "alloc")]
+pub use owned::{Memory, Readiness};
+pub mod poll;
+mod stream;
+pub use stream::StreamReader;
+#[cfg(feature = "alloc")]
+pub use stream::{ReaderStream, SinkWriter};
+mod executor;
+pub use executor::block_on;
+mod genio;
+pub use self::genio::{Blocking, Compat as GenioCompat};
+
+#[cfg(feature = "std")]
+mod allow_std;
+#[cfg(feature = "std")]
+pub use allow_std::AllowStdIo;
 
 #[cfg(feature = "tokio")]
 mod tokio;
 #[cfg(feature = "tokio")]
-pub use self::tokio::Compat as TokioCompat;
+pub use self::tokio::{
+    AsyncReadCompatExt as TokioAsyncReadCompatExt, AsyncWriteCompatExt as TokioAsyncWriteCompatExt,
+    Compat as TokioCompat,
+};
+
+#[cfg(feature = "tokio1")]
+mod tokio1;
+#[cfg(feature = "tokio1")]
+pub use tokio1::{
+    AsyncReadCompatExt as Tokio1AsyncReadCompatExt,
+    AsyncWriteCompatExt as Tokio1AsyncWriteCompatExt, Compat as Tokio1Compat,
+};
 
 #[cfg(feature = "futures")]
 mod futures;
 #[cfg(feature = "futures")]
-pub use self::futures::Compat as FuturesCompat;
+pub use self::futures::{
+    AsyncReadCompatExt as FuturesAsyncReadCompatExt,
+    AsyncWriteCompatExt as FuturesAsyncWriteCompatExt, Compat as FuturesCompat,
+};
+
+#[cfg(feature = "embedded-io")]
+mod embedded;
+#[cfg(all(feature = "embedded-io", feature = "alloc"))]
+pub use embedded::Compat as EmbeddedCompat;
+#[cfg(feature = "embedded-io")]
+pub use embedded::{
+    ReadError as EmbeddedReadError, Reader as EmbeddedReader, WriteError as EmbeddedWriteError,
+    Writer as EmbeddedWriter,
+};
+
+#[cfg(feature = "test-util")]
+mod mock;
+#[cfg(feature = "test-util")]
+pub use mock::{Builder as MockBuilder, Mock};
+#[cfg(feature = "test-util")]
+mod fault;
+#[cfg(feature = "test-util")]
+pub use fault::{FaultyReader, FaultyWriter};
+#[cfg(all(feature = "test-util", feature = "std"))]
+pub mod conformance;
 
 #[derive(Debug)]
 pub struct Empty;
@@ -97,48 +169,16 @@ impl AsyncRead for Repeat {
     }
 }
 
-#[cfg(not(feature = "alloc"))]
+/// Not cancellation safe: if dropped before completing, buffered data that was read but not
+/// yet written is lost. Drive [`poll::poll_copy`] with a caller-owned [`poll::CopyState`]
+/// when the copy may be interrupted.
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Copy<'a, R: ?Sized, W: ?Sized> {
     reader: &'a mut R,
-    read_done: bool,
     writer: &'a mut W,
-    pos: usize,
-    cap: usize,
-    amt: u64,
-    buf: [u8; 2048],
+    state: poll::CopyState,
 }
 
-#[cfg(feature = "alloc")]
-#[must_use = "futures do nothing unless you `.await` or poll them"]
-pub struct Copy<'a, R: ?Sized, W: ?Sized> {
-    reader: &'a mut R,
-    read_done: bool,
-    writer: &'a mut W,
-    pos: usize,
-    cap: usize,
-    amt: u64,
-    buf: alloc::boxed::Box<[u8]>,
-}
-
-#[cfg(feature = "alloc")]
-pub fn copy<'a, R, W>(reader: &'a mut R, writer: &'a mut W) -> Copy<'a, R, W>
-where
-    R: AsyncRead + Unpin + ?Sized,
-    W: AsyncWrite + Unpin + ?Sized,
-{
-    Copy {
-        reader,
-        read_done: false,
-        writer,
-        amt: 0,
-        pos: 0,
-        cap: 0,
-        buf: alloc::boxed::Box::new([0; 2048]),
-    }
-}
-
-#[cfg(not(feature = "alloc"))]
 pub fn copy<'a, R, W>(reader: &'a mut R, writer: &'a mut W) -> Copy<'a, R, W>
 where
     R: AsyncRead + Unpin + ?Sized,
@@ -146,16 +186,11 @@ where
 {
     Copy {
         reader,
-        read_done: false,
         writer,
-        amt: 0,
-        pos: 0,
-        cap: 0,
-        buf: [0; 2048],
+        state: poll::CopyState::new(),
     }
 }
 
-#[derive(Debug)]
 pub enum CopyError<R: AsyncRead + ?Sized, W: AsyncWrite + ?Sized> {
     Read(R::Error),
     Write(W::WriteError),
@@ -163,6 +198,79 @@ pub enum CopyError<R: AsyncRead + ?Sized, W: AsyncWrite + ?Sized> {
     WriteZero,
 }
 
+impl<R, W> fmt::Debug for CopyError<R, W>
+where
+    R: AsyncRead + ?Sized,
+    W: AsyncWrite + ?Sized,
+    R::Error: fmt::Debug,
+    W::WriteError: fmt::Debug,
+    W::FlushError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyError::Read(e) => f.debug_tuple("Read").field(e).finish(),
+            CopyError::Write(e) => f.debug_tuple("Write").field(e).finish(),
+            CopyError::Flush(e) => f.debug_tuple("Flush").field(e).finish(),
+            CopyError::WriteZero => f.write_str("WriteZero"),
+        }
+    }
+}
+
+impl<R, W> fmt::Display for CopyError<R, W>
+where
+    R: AsyncRead + ?Sized,
+    W: AsyncWrite + ?Sized,
+    R::Error: fmt::Display,
+    W::WriteError: fmt::Display,
+    W::FlushError: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyError::Read(e) => e.fmt(f),
+            CopyError::Write(e) => e.fmt(f),
+            CopyError::Flush(e) => e.fmt(f),
+            CopyError::WriteZero => f.write_str("failed to write whole buffer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R, W> std::error::Error for CopyError<R, W>
+where
+    R: AsyncRead + ?Sized,
+    W: AsyncWrite + ?Sized,
+    R::Error: std::error::Error,
+    W::WriteError: std::error::Error,
+    W::FlushError: std::error::Error,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CopyError::Read(e) => e.source(),
+            CopyError::Write(e) => e.source(),
+            CopyError::Flush(e) => e.source(),
+            CopyError::WriteZero => None,
+        }
+    }
+}
+
+impl<R, W> Classify for CopyError<R, W>
+where
+    R: AsyncRead + ?Sized,
+    W: AsyncWrite + ?Sized,
+    R::Error: Classify,
+    W::WriteError: Classify,
+    W::FlushError: Classify,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            CopyError::Read(e) => e.kind(),
+            CopyError::Write(e) => e.kind(),
+            CopyError::Flush(e) => e.kind(),
+            CopyError::WriteZero => ErrorKind::WriteZero,
+        }
+    }
+}
+
 impl<R, W> Future for Copy<'_, R, W>
 where
     R: AsyncRead + Unpin + ?Sized,
@@ -171,37 +279,13 @@ where
     type Output = Result<u64, CopyError<R, W>>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<u64, CopyError<R, W>>> {
-        loop {
-            if self.pos == self.cap && !self.read_done {
-                let me = &mut *self;
-                let n = ready!(Pin::new(&mut *me.reader).poll_read(cx, &mut me.buf))
-                    .map_err(CopyError::Read)?;
-                if n == 0 {
-                    self.read_done = true;
-                } else {
-                    self.pos = 0;
-                    self.cap = n;
-                }
-            }
-
-            while self.pos < self.cap {
-                let me = &mut *self;
-                let i = ready!(Pin::new(&mut *me.writer).poll_write(cx, &me.buf[me.pos..me.cap]))
-                    .map_err(CopyError::Write)?;
-                if i == 0 {
-                    return Poll::Ready(Err(CopyError::WriteZero));
-                } else {
-                    self.pos += i;
-                    self.amt += i as u64;
-                }
-            }
-
-            if self.pos == self.cap && self.read_done {
-                let me = &mut *self;
-                ready!(Pin::new(&mut *me.writer).poll_flush(cx)).map_err(CopyError::Flush)?;
-                return Poll::Ready(Ok(self.amt));
-            }
-        }
+        let me = &mut *self;
+        poll::poll_copy(
+            Pin::new(&mut *me.reader),
+            Pin::new(&mut *me.writer),
+            cx,
+            &mut me.state,
+        )
     }
 }
 
@@ -237,6 +321,13 @@ where
     ) -> Poll<Result<usize, Self::Error>> {
         Pin::new(&mut self.read).poll_read(cx, buf)
     }
+
+    fn poll_read_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.read).poll_read_ready(cx)
+    }
 }
 
 impl<T: AsyncRead, U: AsyncWrite> AsyncWrite for Join<T, U>
@@ -267,6 +358,12 @@ where
     ) -> Poll<Result<(), Self::CloseError>> {
         Pin::new(&mut self.write).poll_close(cx)
     }
+    fn poll_write_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        Pin::new(&mut self.write).poll_write_ready(cx)
+    }
 }
 
 impl<T: AsyncRead, U: AsyncWrite> Split for Join<T, U>
@@ -0,0 +1,324 @@
+use crate::{AsyncRead, AsyncWrite};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::{
+    ascii, fmt, mem,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+enum Action<E> {
+    Read(Vec<u8>),
+    Write(Vec<u8>),
+    Wait,
+    ReadError(E),
+    WriteError(E),
+}
+
+impl<E> fmt::Debug for Action<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Read(data) => write!(f, "read {}", Escaped(data)),
+            Action::Write(data) => write!(f, "write {}", Escaped(data)),
+            Action::Wait => f.write_str("wait"),
+            Action::ReadError(_) => f.write_str("read error"),
+            Action::WriteError(_) => f.write_str("write error"),
+        }
+    }
+}
+
+struct Escaped<'a>(&'a [u8]);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("b\"")?;
+        for byte in self.0 {
+            for c in ascii::escape_default(*byte) {
+                fmt::Write::write_char(f, c as char)?;
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+pub struct Builder<E> {
+    actions: VecDeque<Action<E>>,
+}
+
+impl<E> Builder<E> {
+    pub fn new() -> Self {
+        Builder {
+            actions: VecDeque::new(),
+        }
+    }
+
+    pub fn read(&mut self, data: &[u8]) -> &mut Self {
+        if !data.is_empty() {
+            self.actions.push_back(Action::Read(data.into()));
+        }
+        self
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> &mut Self {
+        if !data.is_empty() {
+            self.actions.push_back(Action::Write(data.into()));
+        }
+        self
+    }
+
+    pub fn wait(&mut self) -> &mut Self {
+        self.actions.push_back(Action::Wait);
+        self
+    }
+
+    pub fn read_error(&mut self, error: E) -> &mut Self {
+        self.actions.push_back(Action::ReadError(error));
+        self
+    }
+
+    pub fn write_error(&mut self, error: E) -> &mut Self {
+        self.actions.push_back(Action::WriteError(error));
+        self
+    }
+
+    pub fn build(&mut self) -> Mock<E> {
+        Mock {
+            actions: mem::take(&mut self.actions),
+            read_waker: None,
+        }
+    }
+}
+
+impl<E> Default for Builder<E> {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl<E> fmt::Debug for Builder<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("actions", &self.actions)
+            .finish()
+    }
+}
+
+/// With the `std` feature, dropping a mock whose script was not fully consumed panics, unless
+/// the thread is already panicking. Without `std` that can't be detected, so the drop check is
+/// skipped to avoid aborting on a double panic; call [`Mock::assert_done`] instead.
+pub struct Mock<E> {
+    actions: VecDeque<Action<E>>,
+    read_waker: Option<Waker>,
+}
+
+impl<E> Unpin for Mock<E> {}
+
+impl<E> Mock<E> {
+    pub fn builder() -> Builder<E> {
+        Builder::new()
+    }
+
+    /// Panics if any of the script has not been consumed yet.
+    pub fn assert_done(&self) {
+        if !self.actions.is_empty() {
+            let remaining = self
+                .actions
+                .iter()
+                .map(|action| alloc::format!("{:?}", action))
+                .collect::<Vec<_>>()
+                .join(", ");
+            panic!("mock has unconsumed script: [{}]", remaining);
+        }
+    }
+
+    fn wake_reader(&mut self) {
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<E> fmt::Debug for Mock<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mock")
+            .field("actions", &self.actions)
+            .finish()
+    }
+}
+
+impl<E> AsyncRead for Mock<E> {
+    type Error = E;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.get_mut();
+
+        match me.actions.front_mut() {
+            None => Poll::Ready(Ok(0)),
+            Some(Action::Read(data)) => {
+                let n = core::cmp::min(data.len(), buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                data.drain(..n);
+                if data.is_empty() {
+                    me.actions.pop_front();
+                }
+                Poll::Ready(Ok(n))
+            }
+            Some(Action::ReadError(_)) => match me.actions.pop_front() {
+                Some(Action::ReadError(e)) => Poll::Ready(Err(e)),
+                _ => unreachable!(),
+            },
+            Some(Action::Wait) => {
+                me.actions.pop_front();
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Some(Action::Write(_)) | Some(Action::WriteError(_)) => {
+                me.read_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<E> AsyncWrite for Mock<E> {
+    type WriteError = E;
+    type FlushError = E;
+    type CloseError = E;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        let me = self.get_mut();
+
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        match me.actions.front_mut() {
+            None => panic!(
+                "mock received unexpected write of {}, the script is exhausted",
+                Escaped(buf)
+            ),
+            Some(Action::Write(expected)) => {
+                let n = core::cmp::min(expected.len(), buf.len());
+                if let Some(offset) = (0..n).find(|&i| expected[i] != buf[i]) {
+                    panic!(
+                        "mock write diverged from the script at byte {}\n  expected: {}\n    actual: {}",
+                        offset,
+                        Escaped(&expected[..n]),
+                        Escaped(&buf[..n])
+                    );
+                }
+                expected.drain(..n);
+                if expected.is_empty() {
+                    me.actions.pop_front();
+                }
+                me.wake_reader();
+                Poll::Ready(Ok(n))
+            }
+            Some(Action::WriteError(_)) => match me.actions.pop_front() {
+                Some(Action::WriteError(e)) => {
+                    me.wake_reader();
+                    Poll::Ready(Err(e))
+                }
+                _ => unreachable!(),
+            },
+            Some(Action::Wait) => {
+                me.actions.pop_front();
+                me.wake_reader();
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Some(action) => panic!(
+                "mock received unexpected write of {}, the script expected {:?}",
+                Escaped(buf),
+                action
+            ),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<E> Drop for Mock<E> {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        {
+            if !std::thread::panicking() {
+                self.assert_done();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt, AsyncWriteExt};
+    use void::Void;
+
+    #[test]
+    fn follows_the_script() {
+        let mut mock = Mock::<Void>::builder()
+            .read(b"hello")
+            .write(b"world")
+            .wait()
+            .read(b"!")
+            .build();
+        let mut buf = [0; 8];
+        assert_eq!(block_on(mock.read(&mut buf)).unwrap(), 5);
+        assert_eq!(&buf[..5], b"hello");
+        block_on(mock.write_all(b"world")).unwrap();
+        assert_eq!(block_on(mock.read(&mut buf)).unwrap(), 1);
+        assert_eq!(block_on(mock.read(&mut buf)).unwrap(), 0);
+        mock.assert_done();
+    }
+
+    #[test]
+    #[should_panic(expected = "mock write diverged from the script at byte 2")]
+    fn diverging_write_panics() {
+        let mut mock = Mock::<Void>::builder().write(b"hello").build();
+        let _ = block_on(mock.write(b"heLlo"));
+    }
+
+    #[test]
+    #[should_panic(expected = "mock received unexpected write of b\"x\", the script expected read")]
+    fn write_instead_of_read_panics() {
+        let mut mock = Mock::<Void>::builder().read(b"hello").build();
+        let _ = block_on(mock.write(b"x"));
+    }
+
+    #[test]
+    #[should_panic(expected = "the script is exhausted")]
+    fn write_past_the_script_panics() {
+        let mut mock = Mock::<Void>::builder().build();
+        let _ = block_on(mock.write(b"x"));
+    }
+
+    #[test]
+    #[should_panic(expected = "mock has unconsumed script: [read b\"lo\", write b\"x\"]")]
+    fn assert_done_reports_the_rest_of_the_script() {
+        let mut mock = Mock::<Void>::builder().read(b"hello").write(b"x").build();
+        let mut buf = [0; 3];
+        assert_eq!(block_on(mock.read(&mut buf)).unwrap(), 3);
+        mock.assert_done();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "mock has unconsumed script: [write b\"x\"]")]
+    fn dropping_an_unfinished_mock_panics() {
+        drop(Mock::<Void>::builder().write(b"x").build());
+    }
+}
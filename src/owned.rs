@@ -0,0 +1,807 @@
+use crate::{AsyncRead, AsyncWrite, AsyncWriteExt, BorrowedBuf, Close, Flush};
+use _futures::ready;
+use bytes::{Bytes, BytesMut};
+use core::{
+    future::Future,
+    mem::MaybeUninit,
+    pin::Pin,
+    slice,
+    task::{Context, Poll},
+};
+
+/// A buffer that can be handed to a completion-based operation.
+///
+/// # Safety
+///
+/// `stable_ptr` must stay valid and keep pointing at the same memory while the buffer is
+/// moved around, until it is dropped or mutably accessed through other means. The first
+/// `bytes_init` bytes behind it must be initialized and `bytes_total` must not exceed the
+/// allocation.
+pub unsafe trait IoBuf: Unpin + 'static {
+    fn stable_ptr(&self) -> *const u8;
+
+    fn bytes_init(&self) -> usize;
+
+    fn bytes_total(&self) -> usize;
+}
+
+/// A buffer that a completion-based operation can write into.
+///
+/// # Safety
+///
+/// `stable_mut_ptr` is subject to the same requirements as [`IoBuf::stable_ptr`].
+pub unsafe trait IoBufMut: IoBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8;
+
+    /// # Safety
+    ///
+    /// The first `pos` bytes of the buffer must be initialized.
+    unsafe fn set_init(&mut self, pos: usize);
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl IoBuf for alloc::vec::Vec<u8> {
+    fn stable_ptr(&self) -> *const u8 {
+        self.as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.capacity()
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl IoBufMut for alloc::vec::Vec<u8> {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.as_mut_ptr()
+    }
+
+    unsafe fn set_init(&mut self, pos: usize) {
+        if self.len() < pos {
+            self.set_len(pos);
+        }
+    }
+}
+
+unsafe impl IoBuf for BytesMut {
+    fn stable_ptr(&self) -> *const u8 {
+        self.as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.capacity()
+    }
+}
+
+unsafe impl IoBufMut for BytesMut {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.as_mut_ptr()
+    }
+
+    unsafe fn set_init(&mut self, pos: usize) {
+        if self.len() < pos {
+            self.set_len(pos);
+        }
+    }
+}
+
+unsafe impl IoBuf for Bytes {
+    fn stable_ptr(&self) -> *const u8 {
+        self.as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.len()
+    }
+}
+
+unsafe impl IoBuf for &'static [u8] {
+    fn stable_ptr(&self) -> *const u8 {
+        self.as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Completion-based reading, where the operation owns the buffer until it finishes.
+///
+/// `read_owned` fills the buffer from its start, up to `bytes_total`, and hands it back
+/// together with the number of bytes read.
+pub trait AsyncReadOwned {
+    type Error;
+
+    type Read<'a, B: IoBufMut>: Future<Output = (Result<usize, Self::Error>, B)> + 'a
+    where
+        Self: 'a;
+
+    fn read_owned<B: IoBufMut>(&mut self, buf: B) -> Self::Read<'_, B>;
+}
+
+/// Completion-based writing, where the operation owns the buffer until it finishes.
+///
+/// `write_owned` writes from the initialized part of the buffer and hands it back together
+/// with the number of bytes written.
+pub trait AsyncWriteOwned {
+    type WriteError;
+    type FlushError;
+    type CloseError;
+
+    type Write<'a, B: IoBuf>: Future<Output = (Result<usize, Self::WriteError>, B)> + 'a
+    where
+        Self: 'a;
+
+    type Flush<'a>: Future<Output = Result<(), Self::FlushError>> + 'a
+    where
+        Self: 'a;
+
+    type Close<'a>: Future<Output = Result<(), Self::CloseError>> + 'a
+    where
+        Self: 'a;
+
+    fn write_owned<B: IoBuf>(&mut self, buf: B) -> Self::Write<'_, B>;
+
+    fn flush_owned(&mut self) -> Self::Flush<'_>;
+
+    fn close_owned(&mut self) -> Self::Close<'_>;
+}
+
+/// Exposes a readiness-based reader or writer through the owned-buffer traits.
+#[derive(Debug)]
+pub struct Owned<T> {
+    inner: T,
+}
+
+impl<T> Owned<T> {
+    pub fn new(inner: T) -> Self {
+        Owned { inner }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadOwned<'a, T: ?Sized, B> {
+    reader: &'a mut T,
+    buf: Option<B>,
+}
+
+impl<T, B> Future for ReadOwned<'_, T, B>
+where
+    T: AsyncRead + Unpin + ?Sized,
+    B: IoBufMut,
+{
+    type Output = (Result<usize, T::Error>, B);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        let buf = me.buf.as_mut().expect("polled after completion");
+
+        let (result, filled) = {
+            let total = buf.bytes_total();
+            let init = buf.bytes_init();
+            let dst = unsafe {
+                slice::from_raw_parts_mut(buf.stable_mut_ptr() as *mut MaybeUninit<u8>, total)
+            };
+            let mut b = BorrowedBuf::uninit(dst);
+            unsafe {
+                b.assume_init(init);
+            }
+            let result = ready!(Pin::new(&mut *me.reader).poll_read_uninit(cx, b.unfilled()));
+            (result, b.filled().len())
+        };
+
+        let mut buf = me.buf.take().unwrap();
+        // `b` never leaves this function and the reader only saw a cursor into it, so `filled`
+        // is bounded by `bytes_total` and every filled byte was written. It is also what gets
+        // reported, rather than the reader's own count.
+        debug_assert!(filled <= buf.bytes_total());
+        unsafe {
+            buf.set_init(filled);
+        }
+        Poll::Ready((result.map(|_| filled), buf))
+    }
+}
+
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteOwned<'a, T: ?Sized, B> {
+    writer: &'a mut T,
+    buf: Option<B>,
+}
+
+impl<T, B> Future for WriteOwned<'_, T, B>
+where
+    T: AsyncWrite + Unpin + ?Sized,
+    B: IoBuf,
+{
+    type Output = (Result<usize, T::WriteError>, B);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let me = &mut *self;
+        let buf = me.buf.as_ref().expect("polled after completion");
+        let src = unsafe { slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init()) };
+        let result = ready!(Pin::new(&mut *me.writer).poll_write(cx, src));
+        Poll::Ready((result, me.buf.take().unwrap()))
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncReadOwned for Owned<T> {
+    type Error = T::Error;
+
+    type Read<'a, B: IoBufMut>
+        = ReadOwned<'a, T, B>
+    where
+        Self: 'a;
+
+    fn read_owned<B: IoBufMut>(&mut self, buf: B) -> Self::Read<'_, B> {
+        ReadOwned {
+            reader: &mut self.inner,
+            buf: Some(buf),
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWriteOwned for Owned<T> {
+    type WriteError = T::WriteError;
+    type FlushError = T::FlushError;
+    type CloseError = T::CloseError;
+
+    type Write<'a, B: IoBuf>
+        = WriteOwned<'a, T, B>
+    where
+        Self: 'a;
+
+    type Flush<'a>
+        = Flush<'a, T>
+    where
+        Self: 'a;
+
+    type Close<'a>
+        = Close<'a, T>
+    where
+        Self: 'a;
+
+    fn write_owned<B: IoBuf>(&mut self, buf: B) -> Self::Write<'_, B> {
+        WriteOwned {
+            writer: &mut self.inner,
+            buf: Some(buf),
+        }
+    }
+
+    fn flush_owned(&mut self) -> Self::Flush<'_> {
+        self.inner.flush()
+    }
+
+    fn close_owned(&mut self) -> Self::Close<'_> {
+        self.inner.close()
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    use super::*;
+    use alloc::{boxed::Box, vec::Vec};
+    use core::{
+        any::Any,
+        cmp,
+        future::{ready, Ready},
+        mem,
+    };
+    use void::Void;
+
+    /// An in-memory owned-buffer reader and writer.
+    ///
+    /// Reads consume the data from the front, writes append to the back.
+    #[derive(Debug, Default)]
+    pub struct Memory {
+        data: Vec<u8>,
+        pos: usize,
+    }
+
+    impl Memory {
+        pub fn new() -> Self {
+            Memory::default()
+        }
+
+        pub fn get_ref(&self) -> &[u8] {
+            &self.data[self.pos..]
+        }
+
+        pub fn into_inner(mut self) -> Vec<u8> {
+            self.data.drain(..self.pos);
+            self.data
+        }
+    }
+
+    impl From<Vec<u8>> for Memory {
+        fn from(data: Vec<u8>) -> Self {
+            Memory { data, pos: 0 }
+        }
+    }
+
+    impl AsyncReadOwned for Memory {
+        type Error = Void;
+
+        type Read<'a, B: IoBufMut> = Ready<(Result<usize, Void>, B)>;
+
+        fn read_owned<B: IoBufMut>(&mut self, mut buf: B) -> Self::Read<'_, B> {
+            let len = cmp::min(self.data.len() - self.pos, buf.bytes_total());
+            unsafe {
+                let dst = buf.stable_mut_ptr();
+                dst.copy_from_nonoverlapping(self.data[self.pos..].as_ptr(), len);
+                buf.set_init(len);
+            }
+            self.pos += len;
+            ready((Ok(len), buf))
+        }
+    }
+
+    impl AsyncWriteOwned for Memory {
+        type WriteError = Void;
+        type FlushError = Void;
+        type CloseError = Void;
+
+        type Write<'a, B: IoBuf> = Ready<(Result<usize, Void>, B)>;
+        type Flush<'a> = Ready<Result<(), Void>>;
+        type Close<'a> = Ready<Result<(), Void>>;
+
+        fn write_owned<B: IoBuf>(&mut self, buf: B) -> Self::Write<'_, B> {
+            let src = unsafe { slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init()) };
+            self.data.extend_from_slice(src);
+            ready((Ok(src.len()), buf))
+        }
+
+        fn flush_owned(&mut self) -> Self::Flush<'_> {
+            ready(Ok(()))
+        }
+
+        fn close_owned(&mut self) -> Self::Close<'_> {
+            ready(Ok(()))
+        }
+    }
+
+    type Erased = Result<usize, Box<dyn Any>>;
+    type Operation<T> = Pin<Box<dyn Future<Output = (T, Vec<u8>, Erased)>>>;
+
+    enum Kind {
+        Read,
+        Write,
+        Flush,
+        Close,
+    }
+
+    enum State<T> {
+        Idle(T),
+        Busy(Kind, Operation<T>),
+        Empty,
+    }
+
+    /// Exposes an owned-buffer reader or writer through the readiness traits.
+    ///
+    /// Each operation is boxed and owns the inner value while it runs, so data passes
+    /// through an internal buffer on both sides.
+    ///
+    /// Once `poll_write` has returned `Pending` the data is already submitted. Retry it with
+    /// the same buffer to learn how much was written; a `poll_flush` or `poll_close` in between
+    /// waits for the write and treats it as committed.
+    pub struct Readiness<T> {
+        state: State<T>,
+        read_buf: Vec<u8>,
+        read_pos: usize,
+        read: Option<Erased>,
+        write_buf: Vec<u8>,
+        written: Option<Erased>,
+        flushed: Option<Erased>,
+        closed: Option<Erased>,
+    }
+
+    impl<T> Unpin for Readiness<T> {}
+
+    fn downcast<E: 'static>(result: Erased) -> Result<usize, E> {
+        result.map_err(|e| *e.downcast().unwrap())
+    }
+
+    fn erase<E: 'static>(result: Result<usize, E>) -> Erased {
+        result.map_err(|e| Box::new(e) as Box<dyn Any>)
+    }
+
+    impl<T> Readiness<T> {
+        pub fn new(inner: T) -> Self {
+            Readiness {
+                state: State::Idle(inner),
+                read_buf: Vec::new(),
+                read_pos: 0,
+                read: None,
+                write_buf: Vec::new(),
+                written: None,
+                flushed: None,
+                closed: None,
+            }
+        }
+
+        pub fn get_ref(&self) -> Option<&T> {
+            match &self.state {
+                State::Idle(inner) => Some(inner),
+                _ => None,
+            }
+        }
+
+        pub fn get_mut(&mut self) -> Option<&mut T> {
+            match &mut self.state {
+                State::Idle(inner) => Some(inner),
+                _ => None,
+            }
+        }
+
+        pub fn into_inner(self) -> Option<T> {
+            match self.state {
+                State::Idle(inner) => Some(inner),
+                _ => None,
+            }
+        }
+
+        fn drive(&mut self, cx: &mut Context) -> Poll<()> {
+            if let State::Busy(kind, operation) = &mut self.state {
+                let (inner, buf, result) = ready!(operation.as_mut().poll(cx));
+                match kind {
+                    Kind::Read => {
+                        self.read_buf = buf;
+                        self.read_pos = 0;
+                        self.read = Some(result);
+                    }
+                    Kind::Write => {
+                        self.write_buf = buf;
+                        self.written = Some(result);
+                    }
+                    Kind::Flush => self.flushed = Some(result),
+                    Kind::Close => self.closed = Some(result),
+                }
+                self.state = State::Idle(inner);
+            }
+            Poll::Ready(())
+        }
+
+        fn start(&mut self, kind: Kind, start: impl FnOnce(T, Vec<u8>) -> Operation<T>) {
+            if let Kind::Flush | Kind::Close = kind {
+                // A write that a flush or close had to wait for is committed; its count must
+                // not be handed to an unrelated later `poll_write`. Errors are kept.
+                if let Some(Ok(_)) = self.written {
+                    self.written = None;
+                }
+            }
+            let buf = match kind {
+                Kind::Read => mem::take(&mut self.read_buf),
+                Kind::Write => mem::take(&mut self.write_buf),
+                Kind::Flush | Kind::Close => Vec::new(),
+            };
+            if let State::Idle(inner) = mem::replace(&mut self.state, State::Empty) {
+                self.state = State::Busy(kind, start(inner, buf));
+            }
+        }
+
+        fn poll_done(
+            &mut self,
+            cx: &mut Context,
+            kind: fn(&mut Self) -> &mut Option<Erased>,
+            start: impl FnOnce(&mut Self),
+        ) -> Poll<Erased> {
+            let mut start = Some(start);
+            loop {
+                if let Some(result) = kind(self).take() {
+                    return Poll::Ready(result);
+                }
+
+                match self.state {
+                    State::Busy(..) => ready!(self.drive(cx)),
+                    State::Idle(_) => match start.take() {
+                        Some(start) => start(self),
+                        None => unreachable!(),
+                    },
+                    State::Empty => unreachable!(),
+                }
+            }
+        }
+    }
+
+    impl<T> AsyncRead for Readiness<T>
+    where
+        T: AsyncReadOwned + 'static,
+        T::Error: 'static,
+    {
+        type Error = T::Error;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let me = self.get_mut();
+
+            if me.read.is_none() && buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let len = buf.len();
+            let result = ready!(me.poll_done(
+                cx,
+                |me| &mut me.read,
+                |me| {
+                    me.start(Kind::Read, |mut inner, mut data| {
+                        data.clear();
+                        data.reserve(len);
+                        Box::pin(async move {
+                            let (result, data) = inner.read_owned(data).await;
+                            (inner, data, erase(result))
+                        })
+                    })
+                }
+            ));
+
+            let n = downcast(result)?;
+            let len = cmp::min(n - me.read_pos, buf.len());
+            buf[..len].copy_from_slice(&me.read_buf[me.read_pos..me.read_pos + len]);
+            me.read_pos += len;
+            if me.read_pos < n {
+                me.read = Some(Ok(n));
+            }
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    impl<T> AsyncWrite for Readiness<T>
+    where
+        T: AsyncWriteOwned + 'static,
+        T::WriteError: 'static,
+        T::FlushError: 'static,
+        T::CloseError: 'static,
+    {
+        type WriteError = T::WriteError;
+        type FlushError = T::FlushError;
+        type CloseError = T::CloseError;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            let result = ready!(self.get_mut().poll_done(
+                cx,
+                |me| &mut me.written,
+                |me| {
+                    me.start(Kind::Write, |mut inner, mut data| {
+                        data.clear();
+                        data.extend_from_slice(buf);
+                        Box::pin(async move {
+                            let (result, data) = inner.write_owned(data).await;
+                            (inner, data, erase(result))
+                        })
+                    })
+                }
+            ));
+            Poll::Ready(downcast(result).map(|n| cmp::min(n, buf.len())))
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::FlushError>> {
+            let result = ready!(self.get_mut().poll_done(
+                cx,
+                |me| &mut me.flushed,
+                |me| {
+                    me.start(Kind::Flush, |mut inner, data| {
+                        Box::pin(async move {
+                            let result = inner.flush_owned().await.map(|_| 0);
+                            (inner, data, erase(result))
+                        })
+                    })
+                }
+            ));
+            Poll::Ready(downcast(result).map(|_| ()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::CloseError>> {
+            let result = ready!(self.get_mut().poll_done(
+                cx,
+                |me| &mut me.closed,
+                |me| {
+                    me.start(Kind::Close, |mut inner, data| {
+                        Box::pin(async move {
+                            let result = inner.close_owned().await.map(|_| 0);
+                            (inner, data, erase(result))
+                        })
+                    })
+                }
+            ));
+            Poll::Ready(downcast(result).map(|_| ()))
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use alloc_impls::{Memory, Readiness};
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{block_on, BorrowedCursor};
+    use alloc::{boxed::Box, vec::Vec};
+    use core::future::poll_fn;
+    use void::Void;
+
+    /// Completes every operation on its second poll.
+    #[derive(Default)]
+    struct Slow {
+        data: Vec<u8>,
+        flushes: usize,
+    }
+
+    fn yield_once() -> impl Future<Output = ()> {
+        let mut yielded = false;
+        poll_fn(move |cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    }
+
+    impl AsyncWriteOwned for Slow {
+        type WriteError = Void;
+        type FlushError = Void;
+        type CloseError = Void;
+
+        type Write<'a, B: IoBuf> = Pin<Box<dyn Future<Output = (Result<usize, Void>, B)> + 'a>>;
+        type Flush<'a> = Pin<Box<dyn Future<Output = Result<(), Void>> + 'a>>;
+        type Close<'a> = Pin<Box<dyn Future<Output = Result<(), Void>> + 'a>>;
+
+        fn write_owned<B: IoBuf>(&mut self, buf: B) -> Self::Write<'_, B> {
+            Box::pin(async move {
+                yield_once().await;
+                let src = unsafe { slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init()) };
+                self.data.extend_from_slice(src);
+                (Ok(src.len()), buf)
+            })
+        }
+
+        fn flush_owned(&mut self) -> Self::Flush<'_> {
+            Box::pin(async move {
+                yield_once().await;
+                self.flushes += 1;
+                Ok(())
+            })
+        }
+
+        fn close_owned(&mut self) -> Self::Close<'_> {
+            Box::pin(async move {
+                yield_once().await;
+                Ok(())
+            })
+        }
+    }
+
+    fn noop_cx() -> Context<'static> {
+        Context::from_waker(_futures::task::noop_waker_ref())
+    }
+
+    #[test]
+    fn write_result_goes_to_the_retried_write() {
+        let mut w = Readiness::new(Slow::default());
+        let mut cx = noop_cx();
+        assert!(Pin::new(&mut w)
+            .poll_write(&mut cx, b"hello world")
+            .is_pending());
+        let n = block_on(poll_fn(|cx| {
+            Pin::new(&mut w).poll_write(cx, b"hello world")
+        }));
+        assert_eq!(n.unwrap(), 11);
+        assert_eq!(w.get_ref().unwrap().data, b"hello world");
+    }
+
+    #[test]
+    fn flush_does_not_leak_write_result() {
+        let mut w = Readiness::new(Slow::default());
+        let mut cx = noop_cx();
+        assert!(Pin::new(&mut w)
+            .poll_write(&mut cx, b"hello world")
+            .is_pending());
+        block_on(poll_fn(|cx| Pin::new(&mut w).poll_flush(cx))).unwrap();
+
+        let n = block_on(poll_fn(|cx| Pin::new(&mut w).poll_write(cx, b"ab")));
+        assert_eq!(n.unwrap(), 2);
+        block_on(poll_fn(|cx| Pin::new(&mut w).poll_close(cx))).unwrap();
+
+        let inner = w.into_inner().unwrap();
+        assert_eq!(inner.data, b"hello worldab");
+        assert_eq!(inner.flushes, 1);
+    }
+
+    #[test]
+    fn write_result_never_exceeds_buffer() {
+        let mut w = Readiness::new(Slow::default());
+        let mut cx = noop_cx();
+        assert!(Pin::new(&mut w)
+            .poll_write(&mut cx, b"hello world")
+            .is_pending());
+        let n = block_on(poll_fn(|cx| Pin::new(&mut w).poll_write(cx, b"ab")));
+        assert!(n.unwrap() <= 2);
+    }
+
+    /// Over-reports and fills a buffer of its own instead of the cursor it was given.
+    struct Evil;
+
+    impl AsyncRead for Evil {
+        type Error = Void;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Void>> {
+            Poll::Ready(Ok(usize::MAX))
+        }
+
+        fn poll_read_uninit(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: BorrowedCursor<'_>,
+        ) -> Poll<Result<usize, Void>> {
+            let mut storage = [0; 64];
+            let mut own = BorrowedBuf::new(&mut storage);
+            own.unfilled().advance(64);
+            Poll::Ready(Ok(usize::MAX))
+        }
+    }
+
+    #[test]
+    fn read_owned_only_trusts_the_cursor() {
+        let mut reader = Owned::new(Evil);
+        let (result, buf) = block_on(reader.read_owned(Vec::with_capacity(8)));
+        assert_eq!(result.unwrap(), 0);
+        assert!(buf.is_empty());
+        assert!(buf.capacity() >= 8);
+    }
+
+    #[test]
+    fn read_owned_fills_vec() {
+        let mut reader = Owned::new(&b"hello"[..]);
+        let (result, buf) = block_on(reader.read_owned(Vec::with_capacity(3)));
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(buf, b"hel");
+    }
+}
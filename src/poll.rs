@@ -0,0 +1,303 @@
+//! Poll-level building blocks for hand-written state machines.
+//!
+//! Each helper keeps its progress in state owned by the caller, so it can be driven from
+//! inside another `poll_*` method. The futures returned by `AsyncReadExt` and
+//! `AsyncWriteExt` are thin wrappers around these.
+
+use crate::{
+    buf, AsyncRead, AsyncWrite, BorrowedBuf, CopyError, PartialInt, ReadExactError, ReadIntError,
+    WriteAllError,
+};
+use _futures::{ready, task::noop_waker_ref};
+use bytes::Buf;
+use core::{
+    fmt,
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Polls `f` once with a waker that does nothing, mapping `Pending` to `Ok(None)`.
+pub(crate) fn try_once<T, E>(
+    f: impl FnOnce(&mut Context) -> Poll<Result<T, E>>,
+) -> Result<Option<T>, E> {
+    let mut cx = Context::from_waker(noop_waker_ref());
+    match f(&mut cx) {
+        Poll::Ready(r) => r.map(Some),
+        Poll::Pending => Ok(None),
+    }
+}
+
+/// Reads until `buf[*pos..]` is full, advancing `pos` as data arrives.
+pub fn poll_read_exact<R: AsyncRead + ?Sized>(
+    mut reader: Pin<&mut R>,
+    cx: &mut Context,
+    buf: &mut [u8],
+    pos: &mut usize,
+) -> Poll<Result<(), ReadExactError<R::Error>>> {
+    while *pos < buf.len() {
+        let n = ready!(reader.as_mut().poll_read(cx, &mut buf[*pos..]))?;
+        if n == 0 {
+            return Poll::Ready(Err(ReadExactError::Eof(*pos)));
+        }
+        *pos += n;
+    }
+    Poll::Ready(Ok(()))
+}
+
+/// Writes until `buf[*pos..]` is empty, advancing `pos` as data is accepted.
+pub fn poll_write_all<W: AsyncWrite + ?Sized>(
+    mut writer: Pin<&mut W>,
+    cx: &mut Context,
+    buf: &[u8],
+    pos: &mut usize,
+) -> Poll<Result<(), WriteAllError<W::WriteError>>> {
+    while *pos < buf.len() {
+        let n = ready!(writer.as_mut().poll_write(cx, &buf[*pos..]))?;
+        if n == 0 {
+            return Poll::Ready(Err(WriteAllError::WriteZero(*pos)));
+        }
+        *pos += n;
+    }
+    Poll::Ready(Ok(()))
+}
+
+macro_rules! poll_read_int {
+    ($($name:ident, $ty:ty, $get:ident;)*) => {
+        $(
+            pub fn $name<R: AsyncRead + ?Sized>(
+                reader: Pin<&mut R>,
+                cx: &mut Context,
+                buf: &mut [u8; core::mem::size_of::<$ty>()],
+                pos: &mut usize,
+            ) -> Poll<Result<$ty, ReadIntError<R::Error>>> {
+                match ready!(poll_read_exact(reader, cx, &mut buf[..], pos)) {
+                    Ok(()) => Poll::Ready(Ok(Buf::$get(&mut &buf[..]))),
+                    Err(ReadExactError::Eof(n)) => {
+                        Poll::Ready(Err(ReadIntError::Eof(PartialInt::new(&buf[..n]))))
+                    }
+                    Err(ReadExactError::Read(e)) => Poll::Ready(Err(ReadIntError::Read(e))),
+                }
+            }
+        )*
+    };
+}
+
+poll_read_int! {
+    poll_read_u8, u8, get_u8;
+    poll_read_i8, i8, get_i8;
+    poll_read_u16_be, u16, get_u16;
+    poll_read_u16_le, u16, get_u16_le;
+    poll_read_i16_be, i16, get_i16;
+    poll_read_i16_le, i16, get_i16_le;
+    poll_read_u32_be, u32, get_u32;
+    poll_read_u32_le, u32, get_u32_le;
+    poll_read_i32_be, i32, get_i32;
+    poll_read_i32_le, i32, get_i32_le;
+    poll_read_u64_be, u64, get_u64;
+    poll_read_u64_le, u64, get_u64_le;
+    poll_read_i64_be, i64, get_i64;
+    poll_read_i64_le, i64, get_i64_le;
+    poll_read_u128_be, u128, get_u128;
+    poll_read_u128_le, u128, get_u128_le;
+    poll_read_i128_be, i128, get_i128;
+    poll_read_i128_le, i128, get_i128_le;
+}
+
+const COPY_BUF_SIZE: usize = 2048;
+
+/// Progress of [`poll_copy`].
+pub struct CopyState {
+    read_done: bool,
+    pos: usize,
+    cap: usize,
+    amt: u64,
+    initialized: usize,
+    #[cfg(feature = "alloc")]
+    buf: alloc::boxed::Box<[MaybeUninit<u8>]>,
+    #[cfg(not(feature = "alloc"))]
+    buf: [MaybeUninit<u8>; COPY_BUF_SIZE],
+}
+
+impl CopyState {
+    pub fn new() -> Self {
+        CopyState {
+            read_done: false,
+            pos: 0,
+            cap: 0,
+            amt: 0,
+            initialized: 0,
+            #[cfg(feature = "alloc")]
+            buf: alloc::boxed::Box::new([MaybeUninit::uninit(); COPY_BUF_SIZE]),
+            #[cfg(not(feature = "alloc"))]
+            buf: [MaybeUninit::uninit(); COPY_BUF_SIZE],
+        }
+    }
+
+    /// Bytes written so far.
+    pub fn amount(&self) -> u64 {
+        self.amt
+    }
+}
+
+impl Default for CopyState {
+    fn default() -> Self {
+        CopyState::new()
+    }
+}
+
+impl fmt::Debug for CopyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyState")
+            .field("read_done", &self.read_done)
+            .field("pos", &self.pos)
+            .field("cap", &self.cap)
+            .field("amt", &self.amt)
+            .finish()
+    }
+}
+
+/// Copies everything from `reader` into `writer` and flushes it, returning the number of
+/// bytes copied.
+pub fn poll_copy<R, W>(
+    mut reader: Pin<&mut R>,
+    mut writer: Pin<&mut W>,
+    cx: &mut Context,
+    state: &mut CopyState,
+) -> Poll<Result<u64, CopyError<R, W>>>
+where
+    R: AsyncRead + ?Sized,
+    W: AsyncWrite + ?Sized,
+{
+    loop {
+        if state.pos == state.cap && !state.read_done {
+            let mut buf = BorrowedBuf::uninit(&mut state.buf[..]);
+            unsafe {
+                buf.assume_init(state.initialized);
+            }
+            ready!(reader.as_mut().poll_read_uninit(cx, buf.unfilled()))
+                .map_err(CopyError::Read)?;
+            let n = buf.filled().len();
+            state.initialized = buf.initialized().len();
+            if n == 0 {
+                state.read_done = true;
+            } else {
+                state.pos = 0;
+                state.cap = n;
+            }
+        }
+
+        while state.pos < state.cap {
+            let i = ready!(writer.as_mut().poll_write(cx, unsafe {
+                buf::slice_assume_init(&state.buf[state.pos..state.cap])
+            }))
+            .map_err(CopyError::Write)?;
+            if i == 0 {
+                return Poll::Ready(Err(CopyError::WriteZero));
+            }
+            state.pos += i;
+            state.amt += i as u64;
+        }
+
+        if state.pos == state.cap && state.read_done {
+            ready!(writer.as_mut().poll_flush(cx)).map_err(CopyError::Flush)?;
+            return Poll::Ready(Ok(state.amt));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{block_on, BorrowedCursor};
+    use alloc::vec::Vec;
+    use core::{cmp, future::poll_fn};
+
+    /// Reads in short chunks and initializes more of the buffer than it fills.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl AsyncRead for Trickle<'_> {
+        type Error = void::Void;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = cmp::min(cmp::min(buf.len(), 300), self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_read_uninit(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            mut buf: BorrowedCursor<'_>,
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = cmp::min(cmp::min(buf.remaining(), 300), self.0.len());
+            let unfilled = buf.initialize_unfilled_to(cmp::min(buf.remaining(), n + 100));
+            unfilled[..n].copy_from_slice(&self.0[..n]);
+            buf.advance(n);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    /// Accepts at most 7 bytes per write and is pending on every other call.
+    #[derive(Default)]
+    struct Sluggish {
+        data: Vec<u8>,
+        ready: bool,
+        flushed: bool,
+    }
+
+    impl AsyncWrite for Sluggish {
+        type WriteError = void::Void;
+        type FlushError = void::Void;
+        type CloseError = void::Void;
+
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let n = cmp::min(buf.len(), 7);
+            self.data.extend_from_slice(&buf[..n]);
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+        ) -> Poll<Result<(), Self::FlushError>> {
+            self.flushed = true;
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn copy_with_short_reads_and_writes() {
+        let data: Vec<u8> = (0..3 * COPY_BUF_SIZE + 11).map(|i| i as u8).collect();
+        let mut reader = Trickle(&data);
+        let mut writer = Sluggish::default();
+        let mut state = CopyState::new();
+        let n = block_on(poll_fn(|cx| {
+            poll_copy(Pin::new(&mut reader), Pin::new(&mut writer), cx, &mut state)
+        }))
+        .unwrap();
+        assert_eq!(n, data.len() as u64);
+        assert_eq!(state.amount(), n);
+        assert_eq!(writer.data, data);
+        assert!(writer.flushed);
+    }
+}
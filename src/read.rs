@@ -1,3 +1,4 @@
+use crate::{BorrowedBuf, BorrowedCursor};
 use _futures::ready;
 use bytes::BufMut;
 use core::ops::DerefMut;
@@ -11,6 +12,13 @@ use genio::Read;
 pub trait AsyncRead {
     type Error;
 
+    /// # Safety
+    ///
+    /// `buf` must be memory the caller is allowed to write to. Implementations that return
+    /// `false` must not read from `buf` in `poll_read`.
+    #[deprecated(
+        note = "no longer called; override `poll_read_uninit` to read into uninitialized memory"
+    )]
     unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [MaybeUninit<u8>]) -> bool {
         for x in buf {
             *x.as_mut_ptr() = 0;
@@ -24,6 +32,34 @@ pub trait AsyncRead {
         buf: &mut [u8],
     ) -> Poll<Result<usize, Self::Error>>;
 
+    /// Reads into the unfilled buffer behind `buf`.
+    ///
+    /// The bytes read must be appended through the cursor, which is authoritative, and the
+    /// returned count must equal the number of bytes appended. Returning without advancing
+    /// the cursor is treated as end of file when it had room left.
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let n = ready!(self.poll_read(cx, buf.initialize_unfilled()))?;
+        buf.advance(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Waits until a call to `poll_read` is expected to make progress.
+    ///
+    /// Readiness may be spurious, so a following read can still return `Pending`. The default
+    /// implementation is always ready.
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        let _ = cx;
+        Poll::Ready(Ok(()))
+    }
+
     fn poll_read_buf<B: BufMut>(
         self: Pin<&mut Self>,
         cx: &mut Context,
@@ -36,22 +72,16 @@ pub trait AsyncRead {
             return Poll::Ready(Ok(0));
         }
 
-        unsafe {
-            let n = {
-                let b = buf.bytes_mut();
-
-                self.prepare_uninitialized_buffer(b);
-
-                let b = &mut *(b as *mut [MaybeUninit<u8>] as *mut [u8]);
-
-                let n = ready!(self.poll_read(cx, b))?;
-                assert!(n <= b.len(), "Bad AsyncRead implementation, more bytes were reported as read than the buffer can hold");
-                n
-            };
+        let n = {
+            let mut b = BorrowedBuf::uninit(buf.bytes_mut());
+            ready!(self.poll_read_uninit(cx, b.unfilled()))?;
+            b.filled().len()
+        };
 
+        unsafe {
             buf.advance_mut(n);
-            Poll::Ready(Ok(n))
         }
+        Poll::Ready(Ok(n))
     }
 }
 
@@ -59,15 +89,25 @@ macro_rules! deref_async_read {
     () => {
         type Error = T::Error;
 
-        unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [MaybeUninit<u8>]) -> bool {
-            (**self).prepare_uninitialized_buffer(buf)
-        }
-
         fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8])
             -> Poll<Result<usize, Self::Error>>
         {
             Pin::new(&mut **self).poll_read(cx, buf)
         }
+
+        fn poll_read_uninit(mut self: Pin<&mut Self>, cx: &mut Context, buf: BorrowedCursor<'_>)
+            -> Poll<Result<usize, Self::Error>>
+        {
+            Pin::new(&mut **self).poll_read_uninit(cx, buf)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (**self).size_hint()
+        }
+
+        fn poll_read_ready(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+            Pin::new(&mut **self).poll_read_ready(cx)
+        }
     }
 }
 
@@ -92,10 +132,6 @@ where
 {
     type Error = <P::Target as AsyncRead>::Error;
 
-    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [MaybeUninit<u8>]) -> bool {
-        (**self).prepare_uninitialized_buffer(buf)
-    }
-
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context,
@@ -103,15 +139,27 @@ where
     ) -> Poll<Result<usize, Self::Error>> {
         self.get_mut().as_mut().poll_read(cx, buf)
     }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        self.get_mut().as_mut().poll_read_uninit(cx, buf)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().as_mut().poll_read_ready(cx)
+    }
 }
 
 impl AsyncRead for &[u8] {
     type Error = <Self as Read>::ReadError;
 
-    unsafe fn prepare_uninitialized_buffer(&self, _buf: &mut [MaybeUninit<u8>]) -> bool {
-        false
-    }
-
     fn poll_read(
         self: Pin<&mut Self>,
         _cx: &mut Context,
@@ -119,4 +167,21 @@ impl AsyncRead for &[u8] {
     ) -> Poll<Result<usize, Self::Error>> {
         Poll::Ready(Read::read(self.get_mut(), buf))
     }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let me = self.get_mut();
+        let len = core::cmp::min(me.len(), buf.remaining());
+        let (head, tail) = me.split_at(len);
+        buf.put_slice(head);
+        *me = tail;
+        Poll::Ready(Ok(len))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
 }
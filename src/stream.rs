@@ -0,0 +1,392 @@
+use crate::AsyncRead;
+use _futures::{ready, Stream};
+use bytes::Buf;
+use core::{
+    cmp,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use pin_project_lite::pin_project;
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct StreamReader<S, B> {
+        #[pin]
+        inner: S,
+        chunk: Option<B>,
+    }
+}
+
+impl<S, B> StreamReader<S, B> {
+    pub fn new(inner: S) -> Self {
+        StreamReader { inner, chunk: None }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, B, E> AsyncRead for StreamReader<S, B>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: Buf,
+{
+    type Error = E;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let mut this = self.project();
+
+        loop {
+            if let Some(chunk) = this.chunk.as_mut() {
+                if chunk.has_remaining() {
+                    let bytes = chunk.bytes();
+                    let len = cmp::min(bytes.len(), buf.len());
+                    buf[..len].copy_from_slice(&bytes[..len]);
+                    chunk.advance(len);
+                    return Poll::Ready(Ok(len));
+                }
+            }
+
+            match ready!(this.inner.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => *this.chunk = Some(chunk),
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => {
+                    *this.chunk = None;
+                    return Poll::Ready(Ok(0));
+                }
+            }
+        }
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        let mut this = self.project();
+
+        loop {
+            if let Some(chunk) = this.chunk.as_ref() {
+                if chunk.has_remaining() {
+                    return Poll::Ready(Ok(()));
+                }
+            }
+
+            match ready!(this.inner.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => *this.chunk = Some(chunk),
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => {
+                    *this.chunk = None;
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    use super::*;
+    use crate::AsyncWrite;
+    use _futures::Sink;
+    use alloc::vec::Vec;
+    use bytes::{buf::BufMutExt, Bytes, BytesMut};
+    use core::marker::PhantomData;
+
+    const DEFAULT_CAPACITY: usize = 4096;
+
+    pin_project! {
+        #[derive(Debug)]
+        #[must_use = "streams do nothing unless polled"]
+        pub struct ReaderStream<R> {
+            #[pin]
+            reader: Option<R>,
+            buf: BytesMut,
+            capacity: usize,
+        }
+    }
+
+    impl<R: AsyncRead> ReaderStream<R> {
+        pub fn new(reader: R) -> Self {
+            ReaderStream::with_capacity(reader, DEFAULT_CAPACITY)
+        }
+
+        pub fn with_capacity(reader: R, capacity: usize) -> Self {
+            assert!(capacity > 0, "ReaderStream chunk size must be non-zero");
+            ReaderStream {
+                reader: Some(reader),
+                buf: BytesMut::new(),
+                capacity,
+            }
+        }
+    }
+
+    impl<R> ReaderStream<R> {
+        pub fn get_ref(&self) -> Option<&R> {
+            self.reader.as_ref()
+        }
+
+        pub fn get_mut(&mut self) -> Option<&mut R> {
+            self.reader.as_mut()
+        }
+
+        pub fn into_inner(self) -> Option<R> {
+            self.reader
+        }
+    }
+
+    impl<R: AsyncRead> Stream for ReaderStream<R> {
+        type Item = Result<Bytes, R::Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+            let mut this = self.project();
+
+            let reader = match this.reader.as_mut().as_pin_mut() {
+                Some(reader) => reader,
+                None => return Poll::Ready(None),
+            };
+
+            if this.buf.capacity() < *this.capacity {
+                this.buf.reserve(*this.capacity);
+            }
+
+            let mut limited = (&mut *this.buf).limit(*this.capacity);
+            match ready!(reader.poll_read_buf(cx, &mut limited)) {
+                Ok(0) => {
+                    this.reader.set(None);
+                    Poll::Ready(None)
+                }
+                Ok(_) => Poll::Ready(Some(Ok(this.buf.split().freeze()))),
+                Err(e) => {
+                    this.reader.set(None);
+                    Poll::Ready(Some(Err(e)))
+                }
+            }
+        }
+    }
+
+    pin_project! {
+        #[derive(Debug)]
+        pub struct SinkWriter<S, B> {
+            #[pin]
+            inner: S,
+            _marker: PhantomData<fn(B)>,
+        }
+    }
+
+    impl<S, B> SinkWriter<S, B> {
+        pub fn new(inner: S) -> Self {
+            SinkWriter {
+                inner,
+                _marker: PhantomData,
+            }
+        }
+
+        pub fn get_ref(&self) -> &S {
+            &self.inner
+        }
+
+        pub fn get_mut(&mut self) -> &mut S {
+            &mut self.inner
+        }
+
+        pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+            self.project().inner
+        }
+
+        pub fn into_inner(self) -> S {
+            self.inner
+        }
+    }
+
+    impl<S, B> AsyncWrite for SinkWriter<S, B>
+    where
+        S: Sink<B>,
+        B: From<Vec<u8>>,
+    {
+        type WriteError = S::Error;
+        type FlushError = S::Error;
+        type CloseError = S::Error;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let mut this = self.project();
+            ready!(this.inner.as_mut().poll_ready(cx))?;
+            this.inner.start_send(B::from(buf.to_vec()))?;
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::FlushError>> {
+            self.project().inner.poll_flush(cx)
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::CloseError>> {
+            self.project().inner.poll_close(cx)
+        }
+
+        fn poll_write_ready(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), Self::WriteError>> {
+            self.project().inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use alloc_impls::{ReaderStream, SinkWriter};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt};
+    use _futures::stream;
+
+    type Chunk = Result<&'static [u8], &'static str>;
+
+    #[test]
+    fn stream_reader_reads_across_chunks() {
+        let chunks: [Chunk; 3] = [Ok(b"abc"), Ok(b""), Ok(b"de")];
+        let mut reader = StreamReader::new(stream::iter(chunks));
+        let mut buf = [0; 2];
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 2);
+        assert_eq!(&buf, b"ab");
+        // The rest of a chunk is returned before the next one is pulled.
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 1);
+        assert_eq!(&buf[..1], b"c");
+        // Empty chunks are skipped rather than read as EOF.
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 2);
+        assert_eq!(&buf, b"de");
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 0);
+    }
+
+    #[test]
+    fn stream_reader_readiness_skips_empty_chunks() {
+        let chunks: [Chunk; 2] = [Ok(b""), Ok(b"a")];
+        let mut reader = StreamReader::new(stream::iter(chunks));
+        block_on(reader.readable()).unwrap();
+        let mut buf = [0; 2];
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 1);
+        block_on(reader.readable()).unwrap();
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 0);
+    }
+
+    #[test]
+    fn stream_reader_returns_stream_errors() {
+        let chunks: [Chunk; 2] = [Ok(b"a"), Err("boom")];
+        let mut reader = StreamReader::new(stream::iter(chunks));
+        let mut buf = [0; 4];
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 1);
+        assert_eq!(block_on(reader.read(&mut buf)), Err("boom"));
+        assert_eq!(block_on(reader.read(&mut buf)).unwrap(), 0);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn reader_stream_ends_at_eof() {
+        use _futures::StreamExt;
+
+        let mut stream = ReaderStream::with_capacity(&b"hello"[..], 2);
+        let mut chunks = alloc::vec::Vec::new();
+        while let Some(chunk) = block_on(stream.next()) {
+            chunks.push(chunk.unwrap());
+        }
+        assert_eq!(chunks, ["he", "ll", "o"]);
+        assert!(stream.get_ref().is_none());
+        assert!(block_on(stream.next()).is_none());
+    }
+
+    /// Holds one item at a time and delivers it a byte per poll while flushing or closing.
+    #[cfg(feature = "alloc")]
+    #[derive(Default)]
+    struct Trickle {
+        pending: alloc::vec::Vec<u8>,
+        delivered: alloc::vec::Vec<u8>,
+        closed: bool,
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Trickle {
+        fn poll_deliver(&mut self, cx: &mut Context) -> Poll<Result<(), &'static str>> {
+            if self.pending.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            let byte = self.pending.remove(0);
+            self.delivered.push(byte);
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl _futures::Sink<alloc::vec::Vec<u8>> for Trickle {
+        type Error = &'static str;
+
+        fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+            self.get_mut().poll_deliver(cx)
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: alloc::vec::Vec<u8>) -> Result<(), Self::Error> {
+            self.get_mut().pending = item;
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+            self.get_mut().poll_deliver(cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+            let me = self.get_mut();
+            ready!(me.poll_deliver(cx))?;
+            me.closed = true;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sink_writer_waits_for_the_sink_to_flush() {
+        use crate::AsyncWriteExt;
+
+        let mut writer = SinkWriter::new(Trickle::default());
+        assert_eq!(block_on(writer.write(b"abc")).unwrap(), 3);
+        assert!(writer.get_ref().delivered.is_empty());
+        block_on(writer.flush()).unwrap();
+        assert_eq!(writer.get_ref().delivered, b"abc");
+
+        assert_eq!(block_on(writer.write(b"de")).unwrap(), 2);
+        block_on(writer.close()).unwrap();
+        assert_eq!(writer.get_ref().delivered, b"abcde");
+        assert!(writer.get_ref().closed);
+    }
+}
@@ -1,94 +1,220 @@
-use crate::{AsyncRead, AsyncWrite};
-use _tokio::io::{AsyncRead as TAsyncRead, AsyncWrite as TAsyncWrite, Error, ErrorKind};
+use crate::{error::into_io_error, AsyncRead, AsyncWrite, Classify};
+use _tokio::io::{AsyncRead as TAsyncRead, AsyncWrite as TAsyncWrite, Error};
 use core::{
     pin::Pin,
     task::{Context, Poll},
 };
+use pin_project_lite::pin_project;
 
-impl<T: Unpin + TAsyncRead> AsyncRead for Compat<T> {
+impl<T: TAsyncRead> AsyncRead for Compat<T> {
     type Error = Error;
 
     fn poll_read(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context,
         buf: &mut [u8],
     ) -> Poll<Result<usize, Self::Error>> {
-        TAsyncRead::poll_read(Pin::new(&mut self.0), cx, buf)
+        TAsyncRead::poll_read(self.project().inner, cx, buf)
     }
 }
 
-impl<T: Unpin + TAsyncWrite> AsyncWrite for Compat<T> {
+impl<T: TAsyncWrite> AsyncWrite for Compat<T> {
     type WriteError = Error;
     type FlushError = Error;
     type CloseError = Error;
 
     fn poll_write(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context,
         buf: &[u8],
     ) -> Poll<Result<usize, Self::WriteError>> {
-        TAsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+        TAsyncWrite::poll_write(self.project().inner, cx, buf)
     }
 
-    fn poll_flush(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context,
-    ) -> Poll<Result<(), Self::FlushError>> {
-        TAsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        TAsyncWrite::poll_flush(self.project().inner, cx)
     }
 
-    fn poll_close(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context,
-    ) -> Poll<Result<(), Self::CloseError>> {
-        TAsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        TAsyncWrite::poll_shutdown(self.project().inner, cx)
     }
 }
 
-pub struct Compat<T>(T);
+pin_project! {
+    #[derive(Debug)]
+    pub struct Compat<T> {
+        #[pin]
+        inner: T,
+    }
+}
 
 impl<T> Compat<T> {
-    pub fn new(input: T) -> Self {
-        Compat(input)
+    pub fn new(inner: T) -> Self {
+        Compat { inner }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
     }
 }
 
-impl<T: Unpin + AsyncWrite> TAsyncWrite for Compat<T>
+impl<T: AsyncWrite> TAsyncWrite for Compat<T>
 where
-    T::WriteError: Into<Box<dyn std::error::Error + Sync + Send>>,
-    T::FlushError: Into<Box<dyn std::error::Error + Sync + Send>>,
-    T::CloseError: Into<Box<dyn std::error::Error + Sync + Send>>,
+    T::WriteError: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
+    T::FlushError: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
+    T::CloseError: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
 {
     fn poll_write(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context,
         buf: &[u8],
     ) -> Poll<Result<usize, Error>> {
-        AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
-            .map_err(|e| Error::new(ErrorKind::Other, e))
+        AsyncWrite::poll_write(self.project().inner, cx, buf).map_err(into_io_error)
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
-        AsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
-            .map_err(|e| Error::new(ErrorKind::Other, e))
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        AsyncWrite::poll_flush(self.project().inner, cx).map_err(into_io_error)
     }
 
-    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
-        AsyncWrite::poll_close(Pin::new(&mut self.0), cx)
-            .map_err(|e| Error::new(ErrorKind::Other, e))
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        AsyncWrite::poll_close(self.project().inner, cx).map_err(into_io_error)
     }
 }
 
-impl<T: Unpin + AsyncRead> TAsyncRead for Compat<T>
+impl<T: AsyncRead> TAsyncRead for Compat<T>
 where
-    T::Error: Into<Box<dyn std::error::Error + Sync + Send>>,
+    T::Error: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
 {
     fn poll_read(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context,
         buf: &mut [u8],
     ) -> Poll<Result<usize, Error>> {
-        AsyncRead::poll_read(Pin::new(&mut self.0), cx, buf)
-            .map_err(|e| Error::new(ErrorKind::Other, e))
+        AsyncRead::poll_read(self.project().inner, cx, buf).map_err(into_io_error)
+    }
+}
+
+pub trait AsyncReadCompatExt: TAsyncRead {
+    fn compat(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: TAsyncRead + ?Sized> AsyncReadCompatExt for T {}
+
+pub trait AsyncWriteCompatExt: TAsyncWrite {
+    fn compat_write(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: TAsyncWrite + ?Sized> AsyncWriteCompatExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, ContractViolation};
+    use core::future::poll_fn;
+    use std::io;
+
+    /// Reads fail with an `io::Error`, writes with one of this crate's errors.
+    struct Failing;
+
+    impl AsyncRead for Failing {
+        type Error = io::Error;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            Poll::Ready(Err(io::Error::new(io::ErrorKind::NotFound, "gone")))
+        }
+    }
+
+    impl AsyncWrite for Failing {
+        type WriteError = ContractViolation;
+        type FlushError = io::Error;
+        type CloseError = ContractViolation;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            Poll::Ready(Err(ContractViolation::WriteAfterClose))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Err(io::ErrorKind::Interrupted.into()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Err(ContractViolation::DataAfterEof { reported: 1 }))
+        }
+    }
+
+    #[test]
+    fn io_errors_pass_through() {
+        let mut compat = Compat::new(Failing);
+        let read = block_on(poll_fn(|cx| {
+            TAsyncRead::poll_read(Pin::new(&mut compat), cx, &mut [0; 4])
+        }))
+        .unwrap_err();
+        assert_eq!(read.kind(), io::ErrorKind::NotFound);
+        assert_eq!(read.to_string(), "gone");
+        let flush = block_on(poll_fn(|cx| {
+            TAsyncWrite::poll_flush(Pin::new(&mut compat), cx)
+        }))
+        .unwrap_err();
+        assert_eq!(flush.kind(), io::ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn crate_errors_keep_kind_and_source() {
+        let mut compat = Compat::new(Failing);
+        let write = block_on(poll_fn(|cx| {
+            TAsyncWrite::poll_write(Pin::new(&mut compat), cx, b"x")
+        }))
+        .unwrap_err();
+        assert_eq!(write.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            *write
+                .into_inner()
+                .unwrap()
+                .downcast::<ContractViolation>()
+                .unwrap(),
+            ContractViolation::WriteAfterClose
+        );
+        let close = block_on(poll_fn(|cx| {
+            TAsyncWrite::poll_shutdown(Pin::new(&mut compat), cx)
+        }))
+        .unwrap_err();
+        assert_eq!(
+            *close
+                .into_inner()
+                .unwrap()
+                .downcast::<ContractViolation>()
+                .unwrap(),
+            ContractViolation::DataAfterEof { reported: 1 }
+        );
     }
 }
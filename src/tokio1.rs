@@ -0,0 +1,390 @@
+use crate::{error::into_io_error, AsyncRead, AsyncWrite, BorrowedBuf, BorrowedCursor, Classify};
+use _futures::ready;
+use _tokio1::io::{AsyncRead as TAsyncRead, AsyncWrite as TAsyncWrite, ReadBuf};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use pin_project_lite::pin_project;
+use std::io::{Error, IoSlice};
+
+impl<T: TAsyncRead> AsyncRead for Compat<T> {
+    type Error = Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let mut buf = ReadBuf::new(buf);
+        TAsyncRead::poll_read(self.project().inner, cx, &mut buf).map_ok(|()| buf.filled().len())
+    }
+
+    fn poll_read_uninit(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        mut buf: BorrowedCursor<'_>,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let initialized = buf.initialized().len();
+        let (filled, initialized) = {
+            let dst = unsafe { buf.unfilled_mut() };
+            let (ptr, len) = (dst.as_ptr(), dst.len());
+            let mut tbuf = ReadBuf::uninit(dst);
+            unsafe {
+                tbuf.assume_init(initialized);
+            }
+            ready!(TAsyncRead::poll_read(self.project().inner, cx, &mut tbuf))?;
+            // A safe tokio reader can swap `tbuf` for a buffer of its own, so its counts are
+            // only trusted while it still points at our memory.
+            assert!(
+                tbuf.filled().as_ptr() == ptr as *const u8 && tbuf.capacity() == len,
+                "tokio reader replaced the ReadBuf it was given"
+            );
+            (tbuf.filled().len(), tbuf.initialized().len())
+        };
+        unsafe {
+            buf.assume_init(initialized);
+        }
+        buf.advance(filled);
+        Poll::Ready(Ok(filled))
+    }
+}
+
+impl<T: TAsyncWrite> AsyncWrite for Compat<T> {
+    type WriteError = Error;
+    type FlushError = Error;
+    type CloseError = Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::WriteError>> {
+        TAsyncWrite::poll_write(self.project().inner, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+        TAsyncWrite::poll_flush(self.project().inner, cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+        TAsyncWrite::poll_shutdown(self.project().inner, cx)
+    }
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Compat<T> {
+        #[pin]
+        inner: T,
+    }
+}
+
+impl<T> Compat<T> {
+    pub fn new(inner: T) -> Self {
+        Compat { inner }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.project().inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: TAsyncWrite> Compat<T> {
+    pub fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    pub fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize, Error>> {
+        TAsyncWrite::poll_write_vectored(self.project().inner, cx, bufs)
+    }
+}
+
+impl<T: AsyncWrite> TAsyncWrite for Compat<T>
+where
+    T::WriteError: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
+    T::FlushError: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
+    T::CloseError: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        AsyncWrite::poll_write(self.project().inner, cx, buf).map_err(into_io_error)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        AsyncWrite::poll_flush(self.project().inner, cx).map_err(into_io_error)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        AsyncWrite::poll_close(self.project().inner, cx).map_err(into_io_error)
+    }
+}
+
+impl<T: AsyncRead> TAsyncRead for Compat<T>
+where
+    T::Error: Classify + Into<Box<dyn std::error::Error + Sync + Send>>,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf,
+    ) -> Poll<Result<(), Error>> {
+        let initialized = buf.initialized().len() - buf.filled().len();
+        let (filled, initialized) = {
+            let mut b = BorrowedBuf::uninit(unsafe { buf.unfilled_mut() });
+            unsafe {
+                b.assume_init(initialized);
+            }
+            ready!(AsyncRead::poll_read_uninit(
+                self.project().inner,
+                cx,
+                b.unfilled()
+            ))
+            .map_err(into_io_error)?;
+            (b.filled().len(), b.initialized().len())
+        };
+        unsafe {
+            buf.assume_init(initialized);
+        }
+        buf.advance(filled);
+        Poll::Ready(Ok(()))
+    }
+}
+
+pub trait AsyncReadCompatExt: TAsyncRead {
+    fn compat(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: TAsyncRead + ?Sized> AsyncReadCompatExt for T {}
+
+pub trait AsyncWriteCompatExt: TAsyncWrite {
+    fn compat_write(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: TAsyncWrite + ?Sized> AsyncWriteCompatExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, AsyncReadExt, AsyncWriteExt, ContractViolation};
+    use core::{future::poll_fn, mem::MaybeUninit};
+    use std::io::ErrorKind;
+
+    /// Fills one byte per read and initializes four more than it fills.
+    struct Bytewise<'a>(&'a [u8]);
+
+    impl AsyncRead for Bytewise<'_> {
+        type Error = Error;
+
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = buf.len().min(self.0.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_read_uninit(
+            mut self: Pin<&mut Self>,
+            _: &mut Context,
+            mut buf: BorrowedCursor<'_>,
+        ) -> Poll<Result<usize, Self::Error>> {
+            let n = buf.remaining().min(self.0.len()).min(1);
+            let extra = buf.remaining().min(n + 4);
+            buf.initialize_unfilled_to(extra)[..n].copy_from_slice(&self.0[..n]);
+            buf.advance(n);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[test]
+    fn tokio_reader_fills_borrowed_buf() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        buf.put_slice(b"x");
+        let mut reader = Compat::new(&b"abc"[..]);
+        let n = block_on(poll_fn(|cx| {
+            AsyncRead::poll_read_uninit(Pin::new(&mut reader), cx, buf.unfilled())
+        }))
+        .unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(buf.filled(), b"xabc");
+        assert!(buf.initialized().len() >= 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "replaced the ReadBuf")]
+    fn tokio_reader_swapping_buffers_is_caught() {
+        struct Evil;
+
+        impl TAsyncRead for Evil {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+                buf: &mut ReadBuf,
+            ) -> Poll<Result<(), Error>> {
+                let own = Box::leak(vec![0; 1024].into_boxed_slice());
+                *buf = ReadBuf::new(own);
+                buf.advance(1024);
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::uninit(&mut storage);
+        let mut reader = Compat::new(Evil);
+        let _ = block_on(poll_fn(|cx| {
+            AsyncRead::poll_read_uninit(Pin::new(&mut reader), cx, buf.unfilled())
+        }));
+    }
+
+    #[test]
+    fn borrowed_buf_reader_fills_tokio_buf() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        buf.put_slice(b"x");
+        let mut reader = Compat::new(Bytewise(b"abc"));
+        block_on(poll_fn(|cx| {
+            TAsyncRead::poll_read(Pin::new(&mut reader), cx, &mut buf)
+        }))
+        .unwrap();
+        assert_eq!(buf.filled(), b"xa");
+        assert_eq!(buf.initialized().len(), 6);
+
+        // The bytes initialized by the last read carry over to the next one.
+        block_on(poll_fn(|cx| {
+            TAsyncRead::poll_read(Pin::new(&mut reader), cx, &mut buf)
+        }))
+        .unwrap();
+        assert_eq!(buf.filled(), b"xab");
+        assert_eq!(buf.initialized().len(), 7);
+    }
+
+    /// Reads fail with an `io::Error`, writes with one of this crate's errors.
+    struct Failing;
+
+    impl AsyncRead for Failing {
+        type Error = Error;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            Poll::Ready(Err(Error::new(ErrorKind::NotFound, "gone")))
+        }
+    }
+
+    impl AsyncWrite for Failing {
+        type WriteError = ContractViolation;
+        type FlushError = Error;
+        type CloseError = ContractViolation;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context,
+            _: &[u8],
+        ) -> Poll<Result<usize, Self::WriteError>> {
+            Poll::Ready(Err(ContractViolation::WriteAfterClose))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::FlushError>> {
+            Poll::Ready(Err(ErrorKind::Interrupted.into()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Self::CloseError>> {
+            Poll::Ready(Err(ContractViolation::DataAfterEof { reported: 1 }))
+        }
+    }
+
+    #[test]
+    fn io_errors_pass_through() {
+        let mut storage = [MaybeUninit::uninit(); 4];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        let mut compat = Compat::new(Failing);
+        let read = block_on(poll_fn(|cx| {
+            TAsyncRead::poll_read(Pin::new(&mut compat), cx, &mut buf)
+        }))
+        .unwrap_err();
+        assert_eq!(read.kind(), ErrorKind::NotFound);
+        assert_eq!(read.to_string(), "gone");
+        let flush = block_on(poll_fn(|cx| {
+            TAsyncWrite::poll_flush(Pin::new(&mut compat), cx)
+        }))
+        .unwrap_err();
+        assert_eq!(flush.kind(), ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn crate_errors_keep_kind_and_source() {
+        let mut compat = Compat::new(Failing);
+        let write = block_on(poll_fn(|cx| {
+            TAsyncWrite::poll_write(Pin::new(&mut compat), cx, b"x")
+        }))
+        .unwrap_err();
+        assert_eq!(write.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            *write
+                .into_inner()
+                .unwrap()
+                .downcast::<ContractViolation>()
+                .unwrap(),
+            ContractViolation::WriteAfterClose
+        );
+        let close = block_on(poll_fn(|cx| {
+            TAsyncWrite::poll_shutdown(Pin::new(&mut compat), cx)
+        }))
+        .unwrap_err();
+        assert_eq!(
+            *close
+                .into_inner()
+                .unwrap()
+                .downcast::<ContractViolation>()
+                .unwrap(),
+            ContractViolation::DataAfterEof { reported: 1 }
+        );
+    }
+
+    #[test]
+    fn tokio_io_is_always_ready() {
+        // Tokio's traits have no readiness hook, so the adapter keeps the default rather than
+        // making readiness-driven callers wait forever.
+        let mut reader = Compat::new(&b"abc"[..]);
+        block_on(reader.readable()).unwrap();
+        let mut writer = Compat::new(Vec::new());
+        block_on(writer.writable()).unwrap();
+    }
+}
@@ -21,6 +21,18 @@ pub trait AsyncWrite {
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>>;
 
+    /// Waits until a call to `poll_write` is expected to make progress.
+    ///
+    /// Readiness may be spurious, so a following write can still return `Pending`. The default
+    /// implementation is always ready.
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        let _ = cx;
+        Poll::Ready(Ok(()))
+    }
+
     fn poll_write_buf<B: Buf>(
         self: Pin<&mut Self>,
         cx: &mut Context,
@@ -58,6 +70,10 @@ macro_rules! deref_async_write {
         fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
             Pin::new(&mut **self).poll_close(cx)
         }
+
+        fn poll_write_ready(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::WriteError>> {
+            Pin::new(&mut **self).poll_write_ready(cx)
+        }
     }
 }
 
@@ -99,6 +115,13 @@ where
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::CloseError>> {
         self.get_mut().as_mut().poll_close(cx)
     }
+
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<(), Self::WriteError>> {
+        self.get_mut().as_mut().poll_write_ready(cx)
+    }
 }
 
 #[cfg(all(feature = "alloc", not(any(feature = "tokio", feature = "futures"))))]